@@ -12,16 +12,50 @@ pub enum Error {
     ParseOutput(String),
     /// Gdb exit status error
     ExitCode(i32),
+    /// Gdb was terminated by signal
+    Signal(i32),
     /// No executable/core found to run under gdb.
     NoFile(String),
+    /// Path contains characters that cannot be passed to gdb command.
+    InvalidPath(String),
+    /// Core file is truncated or isn't an ELF core.
+    InvalidCore(String),
     /// An IO based error
     IO(io::Error),
     /// Error parsing stacktrace
     StacktraceParse(String),
     /// Error parsing mapped files
     MappedFilesParse(String),
+    /// Error parsing registers
+    RegistersParse(String),
+    /// Error parsing disassembly
+    DisassemblyParse(String),
+    /// Error parsing threads output
+    ThreadsParse(String),
+    /// Error parsing sections
+    SectionsParse(String),
+    /// Error parsing source line info
+    LineInfoParse(String),
+    /// Error parsing process info
+    ProcInfoParse(String),
+    /// Error parsing symbol info
+    SymbolInfoParse(String),
+    /// Error parsing frame info
+    FrameInfoParse(String),
+    /// Error parsing shared libraries
+    SharedLibrariesParse(String),
     /// An ParseInt based error
     IntParse(std::num::ParseIntError),
+    /// Gdb printed an error message instead of command result
+    CommandFailed { command: String, message: String },
+    /// Gdb aborted command sequence before the command was executed
+    CommandAborted(String),
+    /// Operation isn't supported for given execution type
+    Unsupported(String),
+    /// Gdb cannot read thread-local variables (no libthread_db or static binary)
+    TlsUnavailable(String),
+    /// Gdb printed warnings to stderr (fail_on_gdb_warning)
+    GdbWarning(Vec<String>),
 }
 
 impl error::Error for Error {
@@ -31,9 +65,26 @@ impl error::Error for Error {
             Error::IntParse(ref pr) => Some(pr),
             Error::ParseOutput(_) => None,
             Error::NoFile(_) => None,
+            Error::InvalidPath(_) => None,
+            Error::InvalidCore(_) => None,
             Error::ExitCode(_) => None,
+            Error::Signal(_) => None,
             Error::StacktraceParse(_) => None,
             Error::MappedFilesParse(_) => None,
+            Error::RegistersParse(_) => None,
+            Error::DisassemblyParse(_) => None,
+            Error::ThreadsParse(_) => None,
+            Error::SectionsParse(_) => None,
+            Error::LineInfoParse(_) => None,
+            Error::ProcInfoParse(_) => None,
+            Error::SymbolInfoParse(_) => None,
+            Error::FrameInfoParse(_) => None,
+            Error::SharedLibrariesParse(_) => None,
+            Error::CommandFailed { .. } => None,
+            Error::CommandAborted(_) => None,
+            Error::Unsupported(_) => None,
+            Error::TlsUnavailable(_) => None,
+            Error::GdbWarning(_) => None,
         }
     }
 }
@@ -56,10 +107,38 @@ impl fmt::Display for Error {
             Error::IO(ref err) => write!(fmt, "{}", err),
             Error::IntParse(ref err) => write!(fmt, "{}", err),
             Error::ExitCode(code) => write!(fmt, "Gdb finished with exit code:{}", code),
+            Error::Signal(signal) => write!(fmt, "Gdb was terminated by signal:{}", signal),
             Error::ParseOutput(ref msg) => write!(fmt, "Gdb parsing output error: {}", msg),
             Error::NoFile(ref msg) => write!(fmt, "File not found: {}", msg),
+            Error::InvalidPath(ref msg) => write!(fmt, "Invalid path: {}", msg),
+            Error::InvalidCore(ref msg) => write!(fmt, "Invalid core file: {}", msg),
             Error::StacktraceParse(ref msg) => write!(fmt, "Error parsing stack trace: {}", msg),
             Error::MappedFilesParse(ref msg) => write!(fmt, "Error parsing mapped files: {}", msg),
+            Error::RegistersParse(ref msg) => write!(fmt, "Error parsing registers: {}", msg),
+            Error::DisassemblyParse(ref msg) => write!(fmt, "Error parsing disassembly: {}", msg),
+            Error::ThreadsParse(ref msg) => write!(fmt, "Error parsing threads: {}", msg),
+            Error::SectionsParse(ref msg) => write!(fmt, "Error parsing sections: {}", msg),
+            Error::LineInfoParse(ref msg) => write!(fmt, "Error parsing line info: {}", msg),
+            Error::ProcInfoParse(ref msg) => write!(fmt, "Error parsing process info: {}", msg),
+            Error::SymbolInfoParse(ref msg) => write!(fmt, "Error parsing symbol info: {}", msg),
+            Error::FrameInfoParse(ref msg) => write!(fmt, "Error parsing frame info: {}", msg),
+            Error::SharedLibrariesParse(ref msg) => {
+                write!(fmt, "Error parsing shared libraries: {}", msg)
+            }
+            Error::CommandFailed {
+                ref command,
+                ref message,
+            } => write!(fmt, "Gdb command '{}' failed: {}", command, message),
+            Error::CommandAborted(ref command) => {
+                write!(fmt, "Gdb command '{}' was not executed", command)
+            }
+            Error::Unsupported(ref msg) => write!(fmt, "Unsupported operation: {}", msg),
+            Error::TlsUnavailable(ref msg) => {
+                write!(fmt, "Thread-local storage is unavailable: {}", msg)
+            }
+            Error::GdbWarning(ref warnings) => {
+                write!(fmt, "Gdb warnings: {}", warnings.join("; "))
+            }
         }
     }
 }
@@ -33,6 +33,7 @@
 //!
 //!     // To run this test: echo 0 | sudo tee /proc/sys/kernel/yama/ptrace_scope
 //!     let result = GdbCommand::new(&ExecType::Remote(&child.id().to_string()))
+//!         .attach_retries(3, Duration::from_millis(10))
 //!         .bt()
 //!         .regs()
 //!         .disassembly()
@@ -45,11 +46,24 @@
 //! ```
 
 use regex::Regex;
+use std::collections::hash_map::RandomState;
+use std::collections::{BTreeMap, HashMap};
 use std::fmt;
-use std::hash::{Hash, Hasher};
+use std::hash::{BuildHasher, Hash, Hasher};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::ops::Range;
+#[cfg(unix)]
+use std::os::unix::fs::OpenOptionsExt;
+#[cfg(unix)]
+use std::os::unix::process::ExitStatusExt;
 use std::path::Path;
 use std::path::PathBuf;
-use std::process::Command;
+use std::process::{self, Command, ExitStatus, Output, Stdio};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::OnceLock;
+use std::thread;
+use std::time::Duration;
+use std::{env, fs, io};
 
 /// `File` struct represents unit (segment) in proccess address space.
 #[derive(Clone, Default, Debug)]
@@ -62,6 +76,10 @@ pub struct File {
     pub offset_in_file: u64,
     /// Full path to binary module.
     pub name: String,
+    /// Target address width in bits (32/64), 0 if unknown.
+    pub address_width: u32,
+    /// Segment permissions (e.g., "r-xp"), empty if gdb doesn't print them.
+    pub perms: String,
 }
 
 impl File {
@@ -80,9 +98,11 @@ impl File {
     pub fn new(base: u64, end: u64, offset: u64, fname: &str) -> Self {
         File {
             base_address: base,
-            end: end,
+            end,
             offset_in_file: offset,
             name: String::from(fname),
+            address_width: 0,
+            perms: String::new(),
         }
     }
 }
@@ -91,8 +111,12 @@ impl fmt::Display for File {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(
             f,
-            "File {{ Base: 0x{:x}, End: 0x{:x}, offset: 0x{:x}, path: {} }}",
-            self.base_address, self.end, self.offset_in_file, self.name
+            "File {{ Base: 0x{:0w$x}, End: 0x{:0w$x}, offset: 0x{:x}, path: {} }}",
+            self.base_address,
+            self.end,
+            self.offset_in_file,
+            self.name,
+            w = (self.address_width / 4) as usize
         )
     }
 }
@@ -127,8 +151,15 @@ impl MappedFiles {
     ///
     /// * 'mapping' - gdb output string with mapped files
     pub fn from_gdb(mapping: &str) -> error::Result<MappedFiles> {
+        // Can not get mappings. Could not read `/proc/1234/maps'
+        if mapping.trim_start().starts_with("Can not get mappings") {
+            return Err(error::Error::MappedFilesParse(format!(
+                "mappings are unavailable: {}",
+                mapping.trim()
+            )));
+        }
         let mut hlp = mapping
-            .split('\n')
+            .lines()
             .map(|s| s.trim().to_string())
             .collect::<Vec<String>>();
         if hlp.len() < 6 {
@@ -143,39 +174,51 @@ impl MappedFiles {
                 format!("cannot parse this string: {}", mapping).to_string(),
             ));
         }
+        // Newer gdb prints permissions column before objfile
+        let columns = if hlp[pos.unwrap()].contains("Perms") {
+            5
+        } else {
+            4
+        };
         hlp.drain(0..pos.unwrap() + 1);
+        hlp.retain(|x| !x.is_empty());
 
         let mut some = Vec::<File>::new();
 
         for x in hlp.iter() {
-            let mut filevec = x
-                .split(' ')
-                .map(|s| s.trim().to_string())
-                .collect::<Vec<String>>();
-            filevec.retain(|x| x != "");
-            if filevec.len() < 4 {
+            // Objfile path may contain spaces, so it is the rest of line after columns
+            let mut filevec = Vec::new();
+            let mut rest = x.as_str();
+            for _ in 0..columns {
+                rest = rest.trim_start();
+                let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+                filevec.push(rest[..end].to_string());
+                rest = &rest[end..];
+            }
+            filevec.retain(|x| !x.is_empty());
+            if filevec.len() < columns {
                 return Err(error::Error::MappedFilesParse(
                     format!("cannot parse this string: {}", mapping).to_string(),
                 ));
             }
+            // Lines after header that aren't mappings (e.g., warnings) can't be parsed
+            let hex = |x: &str| -> error::Result<u64> {
+                match x.strip_prefix("0x") {
+                    Some(x) => Ok(u64::from_str_radix(x, 16)?),
+                    None => Err(error::Error::MappedFilesParse(format!(
+                        "cannot parse this string: {}",
+                        mapping
+                    ))),
+                }
+            };
             let hlp = File {
-                base_address: u64::from_str_radix(
-                    filevec[0].clone().drain(2..).collect::<String>().as_str(),
-                    16,
-                )
-                .unwrap(),
-                end: u64::from_str_radix(
-                    filevec[1].clone().drain(2..).collect::<String>().as_str(),
-                    16,
-                )
-                .unwrap(),
-                offset_in_file: u64::from_str_radix(
-                    filevec[3].clone().drain(2..).collect::<String>().as_str(),
-                    16,
-                )
-                .unwrap(),
-                name: if filevec.len() == 5 {
-                    filevec[4].clone().to_string()
+                base_address: hex(&filevec[0])?,
+                end: hex(&filevec[1])?,
+                offset_in_file: hex(&filevec[3])?,
+                name: rest.trim().to_string(),
+                address_width: 0,
+                perms: if columns == 5 {
+                    filevec[4].clone()
                 } else {
                     String::new()
                 },
@@ -183,7 +226,91 @@ impl MappedFiles {
             some.push(hlp.clone());
         }
 
-        Ok(MappedFiles { files: some })
+        Ok(MappedFiles::with_address_width(some))
+    }
+
+    /// Returns MappedFiles struct built from loaded sections of objfiles. It is used
+    /// when `info proc mappings` isn't supported by target (e.g., qemu-user gdbstub)
+    /// or core. Sections of core file itself are skipped.
+    ///
+    /// # Arguments
+    ///
+    /// * 'sections' - gdb output string with sections of all objfiles
+    pub fn from_sections(sections: &str) -> error::Result<MappedFiles> {
+        // Exec file: `/tmp/test', file type elf64-x86-64.
+        // Object file: /lib/x86_64-linux-gnu/libc.so.6
+        // Core file: `/tmp/core', file type elf64-x86-64.
+        let objfile_re = Regex::new(r"^\s*(Exec|Object|Core) file:\s+`?([^'`,]+)'?").unwrap();
+        let section_re = section_regex();
+        let mut files = Vec::new();
+        let mut name = String::new();
+        let mut core = false;
+        for line in sections.lines() {
+            if let Some(caps) = objfile_re.captures(line) {
+                core = &caps[1] == "Core";
+                name = caps[2].trim().to_string();
+            } else if let Some(caps) = section_re.captures(line) {
+                if core || !caps[5].split_whitespace().any(|f| f == "ALLOC") {
+                    continue;
+                }
+                files.push(File {
+                    base_address: u64::from_str_radix(&caps[1], 16)?,
+                    end: u64::from_str_radix(&caps[2], 16)?,
+                    offset_in_file: u64::from_str_radix(&caps[3], 16)?,
+                    name: name.clone(),
+                    address_width: 0,
+                    perms: String::new(),
+                });
+            }
+        }
+        if files.is_empty() {
+            return Err(error::Error::MappedFilesParse(format!(
+                "cannot parse this string: {}",
+                sections
+            )));
+        }
+        Ok(MappedFiles::with_address_width(files))
+    }
+
+    /// Returns MappedFiles struct from `remote_mappings` command results. Sections
+    /// are used if target doesn't support `info proc mappings` or gdb can't get
+    /// mappings from core.
+    ///
+    /// # Arguments
+    ///
+    /// * 'mappings' - gdb output string with mapped files
+    ///
+    /// * 'sections' - gdb output string with sections of all objfiles
+    pub fn from_remote(mappings: &str, sections: &str) -> error::Result<MappedFiles> {
+        MappedFiles::from_gdb(mappings).or_else(|_| MappedFiles::from_sections(sections))
+    }
+
+    /// Returns MappedFiles struct with address width set for all files.
+    fn with_address_width(mut files: Vec<File>) -> MappedFiles {
+        // gdb doesn't pad addresses in mappings, so guess width by the highest address
+        let width = if files.iter().all(|f| f.end <= 0x1_0000_0000) {
+            32
+        } else {
+            64
+        };
+        files.iter_mut().for_each(|f| f.address_width = width);
+        MappedFiles { files }
+    }
+
+    /// Method returns executable segments. It returns nothing if permissions
+    /// are unknown (gdb older than 12 or mappings built from sections).
+    pub fn executable_regions(&self) -> impl Iterator<Item = &File> {
+        self.files
+            .iter()
+            .filter(|x| x.perms.chars().nth(2) == Some('x'))
+    }
+
+    /// Method returns writable segments. It returns nothing if permissions
+    /// are unknown (gdb older than 12 or mappings built from sections).
+    pub fn writable_regions(&self) -> impl Iterator<Item = &File> {
+        self.files
+            .iter()
+            .filter(|x| x.perms.chars().nth(1) == Some('w'))
     }
 
     /// Method determines which file contains the address
@@ -194,11 +321,544 @@ impl MappedFiles {
     pub fn find(&self, addr: u64) -> Option<File> {
         self.files
             .iter()
-            .find(|&x| (x.base_address < addr as u64) && (x.end > addr as u64))
+            .find(|&x| (x.base_address <= addr) && (x.end > addr))
+            .cloned()
+    }
+}
+
+/// `StopReason` struct represents signal that stopped the program.
+#[derive(Clone, Default, Debug, PartialEq, Eq)]
+pub struct StopReason {
+    /// Signal name (SIGSEGV)
+    pub signal: String,
+    /// Signal description (Segmentation fault)
+    pub description: String,
+    /// Id of thread that received the signal (for multithreaded programs)
+    pub thread_id: Option<String>,
+    /// Name of thread that received the signal
+    pub thread_name: Option<String>,
+}
+
+impl StopReason {
+    /// Returns StopReason struct if gdb output contains signal line
+    ///
+    /// # Arguments
+    ///
+    /// * 'output' - gdb output (`raw`) with program run or core loading
+    pub fn from_gdb(output: &str) -> Option<StopReason> {
+        // Program received signal SIGSEGV, Segmentation fault.
+        // Thread 7 "worker" received signal SIGABRT, Aborted.
+        // Program terminated with signal SIGABRT, Aborted.
+        let re = Regex::new(
+            r#"(?m)^(?:Thread ([\d.]+)(?: "([^"]*)")?|Program) (?:received|terminated with) signal (\w+), (.*?)\.?\r?$"#,
+        )
+        .unwrap();
+        let caps = re.captures(output)?;
+        Some(StopReason {
+            signal: caps[3].to_string(),
+            description: caps[4].to_string(),
+            thread_id: caps.get(1).map(|x| x.as_str().to_string()),
+            thread_name: caps.get(2).map(|x| x.as_str().to_string()),
+        })
+    }
+}
+
+/// `LineInfo` struct represents address range of source line (info line).
+#[derive(Clone, Default, Debug, PartialEq, Eq)]
+pub struct LineInfo {
+    /// Source file
+    pub file: String,
+    /// Line number
+    pub line: u64,
+    /// Start address of line code
+    pub start: u64,
+    /// End address of line code (equal to start if line contains no code)
+    pub end: u64,
+}
+
+impl LineInfo {
+    /// Returns LineInfo struct
+    ///
+    /// # Arguments
+    ///
+    /// * 'output' - gdb output string of info line command
+    pub fn from_gdb(output: &str) -> error::Result<LineInfo> {
+        // Line 5 of "test.c" starts at address 0x1149 <main> and ends at 0x1151 <main+8>.
+        // Line 3 of "test.c" is at address 0x1149 <main> but contains no code.
+        let re = Regex::new(
+            r#"Line (\d+) of "([^"]*)"\s+(?:starts at|is at) address 0x([0-9a-fA-F]+)(?: <[^>]*>)?(?: and ends at 0x([0-9a-fA-F]+))?"#,
+        )
+        .unwrap();
+        let caps = match re.captures(output) {
+            Some(caps) => caps,
+            None => {
+                return Err(error::Error::LineInfoParse(format!(
+                    "cannot get line info from this string: {}",
+                    output
+                )))
+            }
+        };
+        let start = u64::from_str_radix(&caps[3], 16)?;
+        Ok(LineInfo {
+            file: caps[2].to_string(),
+            line: caps[1].parse::<u64>()?,
+            start,
+            end: match caps.get(4) {
+                Some(end) => u64::from_str_radix(end.as_str(), 16)?,
+                None => start,
+            },
+        })
+    }
+}
+
+/// `SymbolInfo` struct represents symbol that contains address (info symbol).
+#[derive(Clone, Default, Debug, PartialEq, Eq)]
+pub struct SymbolInfo {
+    /// Symbol name
+    pub symbol: String,
+    /// Offset of address from symbol start
+    pub offset: u64,
+    /// Section name
+    pub section: String,
+    /// Objfile path (gdb omits it for the only objfile)
+    pub module: Option<String>,
+}
+
+impl SymbolInfo {
+    /// Returns SymbolInfo struct
+    ///
+    /// # Arguments
+    ///
+    /// * 'output' - gdb output string of info symbol command
+    pub fn from_gdb(output: &str) -> error::Result<SymbolInfo> {
+        // printf + 16 in section .text of /lib/x86_64-linux-gnu/libc.so.6
+        // main in section .text
+        let re = Regex::new(r"^(\S+)(?: \+ (\d+))? in section (\S+)(?: of (.+))?$").unwrap();
+        let caps = match re.captures(output.trim()) {
+            Some(caps) => caps,
+            None => {
+                return Err(error::Error::SymbolInfoParse(format!(
+                    "cannot get symbol info from this string: {}",
+                    output
+                )))
+            }
+        };
+        Ok(SymbolInfo {
+            symbol: caps[1].to_string(),
+            offset: match caps.get(2) {
+                Some(offset) => offset.as_str().parse::<u64>()?,
+                None => 0,
+            },
+            section: caps[3].to_string(),
+            module: caps.get(4).map(|x| x.as_str().to_string()),
+        })
+    }
+}
+
+/// `FrameInfo` struct represents unwinding details of selected frame (info frame).
+#[derive(Clone, Default, Debug, PartialEq, Eq)]
+pub struct FrameInfo {
+    /// Frame level (0 is the innermost frame)
+    pub level: u32,
+    /// Frame address (canonical frame address, CFA)
+    pub cfa: u64,
+    /// Program counter of the frame
+    pub pc: u64,
+    /// Saved program counter (return address)
+    pub saved_pc: Option<u64>,
+    /// Address of the frame that called this frame
+    pub caller: Option<u64>,
+    /// Address of the frame called by this frame
+    pub callee: Option<u64>,
+    /// Saved registers and stack addresses where they are saved
+    pub saved_registers: BTreeMap<String, u64>,
+}
+
+impl FrameInfo {
+    /// Returns FrameInfo struct
+    ///
+    /// # Arguments
+    ///
+    /// * 'output' - gdb output string of info frame command
+    pub fn from_gdb(output: &str) -> error::Result<FrameInfo> {
+        // Stack level 0, frame at 0x7fffffffe0f0:
+        //  rip = 0x555555555169 in main (/tmp/test.c:5); saved rip = 0x7ffff7df0083
+        //  caller of frame at 0x7fffffffe0c0
+        //  called by frame at 0x7fffffffe1c0
+        //  Saved registers:
+        //   rbp at 0x7fffffffe0e0, rip at 0x7fffffffe0e8
+        let level_re = Regex::new(r"Stack level (\d+), frame at 0x([0-9a-fA-F]+)").unwrap();
+        let Some(caps) = level_re.captures(output) else {
+            return Err(error::Error::FrameInfoParse(format!(
+                "cannot get frame info from this string: {}",
+                output
+            )));
+        };
+        let mut info = FrameInfo {
+            level: caps[1].parse::<u32>()?,
+            cfa: u64::from_str_radix(&caps[2], 16)?,
+            ..Default::default()
+        };
+        let pc_re = Regex::new(r"^\s*\w+ = 0x([0-9a-fA-F]+)").unwrap();
+        let saved_pc_re = Regex::new(r"saved \w+ = 0x([0-9a-fA-F]+)").unwrap();
+        let register_re = Regex::new(r"(\w+) at 0x([0-9a-fA-F]+)").unwrap();
+        let mut saved_registers = false;
+        for line in output.lines() {
+            if saved_registers {
+                for caps in register_re.captures_iter(line) {
+                    info.saved_registers
+                        .insert(caps[1].to_string(), u64::from_str_radix(&caps[2], 16)?);
+                }
+            } else if let Some(addr) = line.trim().strip_prefix("caller of frame at 0x") {
+                info.callee = Some(u64::from_str_radix(addr, 16)?);
+            } else if let Some(addr) = line.trim().strip_prefix("called by frame at 0x") {
+                info.caller = Some(u64::from_str_radix(addr, 16)?);
+            } else if line.trim() == "Saved registers:" {
+                saved_registers = true;
+            } else if let Some(caps) = pc_re.captures(line) {
+                info.pc = u64::from_str_radix(&caps[1], 16)?;
+                if let Some(caps) = saved_pc_re.captures(line) {
+                    info.saved_pc = Some(u64::from_str_radix(&caps[1], 16)?);
+                }
+            }
+        }
+        Ok(info)
+    }
+}
+
+/// `ProcInfo` struct represents process snapshot (info proc all).
+#[derive(Clone, Default, Debug)]
+pub struct ProcInfo {
+    /// Command line
+    pub cmdline: Option<String>,
+    /// Current working directory
+    pub cwd: Option<String>,
+    /// Executable path
+    pub exe: Option<String>,
+    /// Mapped files
+    pub mappings: Option<MappedFiles>,
+    /// Process status fields (Name, State, Pid, ...)
+    pub status: BTreeMap<String, String>,
+}
+
+impl ProcInfo {
+    /// Returns ProcInfo struct
+    ///
+    /// # Arguments
+    ///
+    /// * 'output' - gdb output string of info proc all command
+    pub fn from_gdb(output: &str) -> error::Result<ProcInfo> {
+        let mut info = ProcInfo::default();
+        let lines = output.lines().map(|x| x.trim()).collect::<Vec<&str>>();
+        let mut in_mappings = false;
+        let mut mappings_end = None;
+        for (i, line) in lines.iter().enumerate() {
+            if line.contains("Start Addr") {
+                in_mappings = true;
+                continue;
+            }
+            if in_mappings {
+                if line.starts_with("0x") {
+                    mappings_end = Some(i + 1);
+                    continue;
+                }
+                in_mappings = false;
+            }
+            if let Some((key, value)) = line.split_once(" = ") {
+                let value = value.trim_matches('\'').to_string();
+                match key {
+                    "cmdline" => info.cmdline = Some(value),
+                    "cwd" => info.cwd = Some(value),
+                    "exe" => info.exe = Some(value),
+                    _ => {}
+                }
+            } else if let Some((key, value)) = line.split_once(':') {
+                if !key.is_empty() && !key.contains(' ') {
+                    info.status
+                        .insert(key.to_string(), value.trim().to_string());
+                }
+            }
+        }
+        if let Some(end) = mappings_end {
+            info.mappings = Some(MappedFiles::from_gdb(&lines[..end].join("\n"))?);
+        }
+        if info.cmdline.is_none()
+            && info.exe.is_none()
+            && info.mappings.is_none()
+            && info.status.is_empty()
+        {
+            return Err(error::Error::ProcInfoParse(format!(
+                "cannot get process info from this string: {}",
+                output
+            )));
+        }
+        Ok(info)
+    }
+}
+
+/// `Section` struct represents ELF section of objfile.
+#[derive(Clone, Default, Debug)]
+pub struct Section {
+    /// Start address of section
+    pub start: u64,
+    /// End address of section
+    pub end: u64,
+    /// Offset in file
+    pub offset_in_file: u64,
+    /// Section name
+    pub name: String,
+    /// Section flags (ALLOC, LOAD, READONLY, CODE, ...)
+    pub flags: Vec<String>,
+}
+
+///`Sections` all sections from gdb (maintenance info sections).
+#[derive(Clone, Default, Debug)]
+pub struct Sections {
+    /// Vector of sections
+    pub sections: Vec<Section>,
+}
+
+impl Sections {
+    /// Returns Sections struct
+    ///
+    /// # Arguments
+    ///
+    /// * 'sections' - gdb output string with sections
+    pub fn from_gdb(sections: &str) -> error::Result<Sections> {
+        let re = section_regex();
+        let mut result = Vec::new();
+        for line in sections.lines() {
+            if let Some(caps) = re.captures(line) {
+                result.push(Section {
+                    start: u64::from_str_radix(&caps[1], 16)?,
+                    end: u64::from_str_radix(&caps[2], 16)?,
+                    offset_in_file: u64::from_str_radix(&caps[3], 16)?,
+                    name: caps[4].to_string(),
+                    flags: caps[5].split_whitespace().map(|f| f.to_string()).collect(),
+                });
+            }
+        }
+        if result.is_empty() {
+            return Err(error::Error::SectionsParse(format!(
+                "cannot parse this string: {}",
+                sections
+            )));
+        }
+        Ok(Sections { sections: result })
+    }
+
+    /// Method determines which section contains the address
+    ///
+    /// # Arguments
+    ///
+    /// * 'addr' - given address
+    pub fn find(&self, addr: u64) -> Option<Section> {
+        self.sections
+            .iter()
+            .find(|&x| (x.start <= addr) && (x.end > addr))
+            .cloned()
+    }
+}
+
+/// `SharedLibrary` struct represents loaded shared library (info sharedlibrary).
+#[derive(Clone, Default, Debug, PartialEq, Eq)]
+pub struct SharedLibrary {
+    /// Start address of library code (0 if library isn't loaded)
+    pub from: u64,
+    /// End address of library code (0 if library isn't loaded)
+    pub to: u64,
+    /// Gdb has read library symbols
+    pub syms_read: bool,
+    /// Library symbols include debugging information
+    pub debug_info: bool,
+    /// Library path
+    pub name: String,
+}
+
+///`SharedLibraries` all shared libraries from gdb (info sharedlibrary).
+#[derive(Clone, Default, Debug)]
+pub struct SharedLibraries {
+    /// Vector of shared libraries
+    pub libraries: Vec<SharedLibrary>,
+}
+
+impl SharedLibraries {
+    /// Returns SharedLibraries struct
+    ///
+    /// # Arguments
+    ///
+    /// * 'output' - gdb output string of info sharedlibrary command
+    pub fn from_gdb(output: &str) -> error::Result<SharedLibraries> {
+        // 0x00007ffff7dad700  0x00007ffff7f3f93d  Yes (*)     /lib/x86_64-linux-gnu/libc.so.6
+        //                                         No          /lib/libfoo.so
+        let re = Regex::new(
+            r"^(?:\s*0x([0-9a-fA-F]+)\s+0x([0-9a-fA-F]+))?\s+(Yes|No)( \(\*\))?\s+(.+)$",
+        )
+        .unwrap();
+        let mut libraries = Vec::new();
+        for line in output.lines() {
+            if let Some(caps) = re.captures(line) {
+                libraries.push(SharedLibrary {
+                    from: match caps.get(1) {
+                        Some(from) => u64::from_str_radix(from.as_str(), 16)?,
+                        None => 0,
+                    },
+                    to: match caps.get(2) {
+                        Some(to) => u64::from_str_radix(to.as_str(), 16)?,
+                        None => 0,
+                    },
+                    syms_read: &caps[3] == "Yes",
+                    debug_info: &caps[3] == "Yes" && caps.get(4).is_none(),
+                    name: caps[5].trim_end().to_string(),
+                });
+            }
+        }
+        if libraries.is_empty() && !output.contains("No shared libraries loaded") {
+            return Err(error::Error::SharedLibrariesParse(format!(
+                "cannot parse this string: {}",
+                output
+            )));
+        }
+        Ok(SharedLibraries { libraries })
+    }
+
+    /// Method determines which library contains the address
+    ///
+    /// # Arguments
+    ///
+    /// * 'addr' - given address
+    pub fn find(&self, addr: u64) -> Option<SharedLibrary> {
+        self.libraries
+            .iter()
+            .find(|&x| (x.from <= addr) && (x.to > addr))
             .cloned()
     }
 }
 
+/// `Registers` struct represents register values from gdb (info registers).
+#[derive(Clone, Default, Debug)]
+pub struct Registers {
+    /// Register values by name
+    pub regs: HashMap<String, u64>,
+}
+
+impl Registers {
+    /// Returns Registers struct
+    ///
+    /// # Arguments
+    ///
+    /// * 'registers' - gdb output string with registers (i r)
+    pub fn from_gdb(registers: &str) -> error::Result<Registers> {
+        let mut regs = HashMap::new();
+        for line in registers.lines() {
+            // rip            0x555555555189      0x555555555189 <main+4>
+            let mut parts = line.split_whitespace();
+            let (name, value) = match (parts.next(), parts.next()) {
+                (Some(name), Some(value)) => (name, value),
+                _ => continue,
+            };
+            // Skip vector registers and other non-scalar values
+            if let Some(value) = value.strip_prefix("0x") {
+                regs.insert(name.to_string(), u64::from_str_radix(value, 16)?);
+            }
+        }
+        if regs.is_empty() {
+            return Err(error::Error::RegistersParse(format!(
+                "cannot parse this string: {}",
+                registers
+            )));
+        }
+        Ok(Registers { regs })
+    }
+
+    /// Method returns register value by name
+    ///
+    /// # Arguments
+    ///
+    /// * 'name' - register name
+    pub fn get(&self, name: &str) -> Option<u64> {
+        self.regs.get(name).copied()
+    }
+
+    /// Method returns instruction pointer value (rip, eip or pc)
+    pub fn instruction_pointer(&self) -> Option<u64> {
+        ["rip", "eip", "pc"].iter().find_map(|name| self.get(name))
+    }
+
+    /// Method returns stack pointer value (rsp, esp or sp)
+    pub fn stack_pointer(&self) -> Option<u64> {
+        ["rsp", "esp", "sp"].iter().find_map(|name| self.get(name))
+    }
+}
+
+/// `Instruction` struct represents one disassembled instruction (x/Ni $pc).
+#[derive(Clone, Default, Debug, PartialEq, Eq)]
+pub struct Instruction {
+    /// Instruction address
+    pub address: u64,
+    /// Symbol with offset (main+8), empty if unknown
+    pub symbol: String,
+    /// Instruction mnemonic without prefixes (rep, lock, ...)
+    pub mnemonic: String,
+    /// Instruction operands
+    pub operands: Vec<String>,
+    /// Instruction is at program counter (`=>` marker)
+    pub current: bool,
+}
+
+///`Disassembly` instructions from gdb (x/Ni $pc).
+#[derive(Clone, Default, Debug)]
+pub struct Disassembly {
+    /// Vector of instructions
+    pub instructions: Vec<Instruction>,
+}
+
+impl Disassembly {
+    /// Returns Disassembly struct. Instructions stop at unreadable memory.
+    ///
+    /// # Arguments
+    ///
+    /// * 'disassembly' - gdb output string of x/Ni command
+    pub fn from_gdb(disassembly: &str) -> error::Result<Disassembly> {
+        // => 0x555555555131 <main+8>:	mov    DWORD PTR [rax],0x1
+        //    0x555555555137 <main+14>:	ret
+        let re = Regex::new(r"^(=>)?\s*0x([0-9a-fA-F]+)(?: <(.+)>)?:\s+(.*)$").unwrap();
+        let mut instructions = Vec::new();
+        for line in disassembly.lines() {
+            let Some(caps) = re.captures(line.trim_end()) else {
+                continue;
+            };
+            // => 0x0:	Cannot access memory at address 0x0
+            if caps[4].starts_with("Cannot access memory") {
+                break;
+            }
+            let (mnemonic, operands) = split_instruction(&caps[4]);
+            instructions.push(Instruction {
+                address: u64::from_str_radix(&caps[2], 16)?,
+                symbol: caps
+                    .get(3)
+                    .map(|x| x.as_str().to_string())
+                    .unwrap_or_default(),
+                mnemonic,
+                operands,
+                current: caps.get(1).is_some(),
+            });
+        }
+        if instructions.is_empty() && !disassembly.contains("Cannot access memory") {
+            return Err(error::Error::DisassemblyParse(format!(
+                "cannot parse this string: {}",
+                disassembly
+            )));
+        }
+        Ok(Disassembly { instructions })
+    }
+
+    /// Method returns instruction at program counter
+    pub fn current(&self) -> Option<&Instruction> {
+        self.instructions.iter().find(|x| x.current)
+    }
+}
+
 /// 'ModuleInfo' enum represents the name of the module or contains information about the module.
 #[derive(Clone, Debug)]
 pub enum ModuleInfo {
@@ -217,6 +877,14 @@ pub struct StacktraceEntry {
     pub module: ModuleInfo,
     /// Debug information
     pub debug: DebugInfo,
+    /// Target address width in bits (32/64), 0 if unknown.
+    pub address_width: u32,
+    /// Function arguments (name, value) if gdb printed them
+    pub arguments: Vec<(String, String)>,
+    /// Frame number (#N) from gdb output
+    pub frame_number: u32,
+    /// Module build id printed by ASan: `(BuildId: ...)`
+    pub build_id: Option<String>,
 }
 
 /// `FrameDebug` struct represents the debug information of one frame in stack trace.
@@ -231,11 +899,25 @@ pub struct DebugInfo {
     pub offset_in_line: u64,
 }
 
+/// `CrashLocation` enum represents the location where the program crashed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CrashLocation {
+    /// Source location from debug information
+    Source(DebugInfo),
+    /// Offset of the crash address in module file (no debug information)
+    Module { path: String, offset: u64 },
+    /// Crash address isn't in mapped modules, function name is known if gdb printed it
+    Address {
+        address: u64,
+        function: Option<String>,
+    },
+}
+
 impl fmt::Display for StacktraceEntry {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(
             f,
-            "Address: 0x{:x}, ModuleInfo: {}, DebugInfo: {}",
+            "Address: 0x{:0w$x}, ModuleInfo: {}, DebugInfo: {}",
             self.address,
             match self.module.clone() {
                 ModuleInfo::Name(x) => x,
@@ -251,6 +933,7 @@ impl fmt::Display for StacktraceEntry {
                 .to_string(),
                 None => self.debug.file_path.clone(),
             },
+            w = (self.address_width / 4) as usize
         )
     }
 }
@@ -261,16 +944,16 @@ impl PartialEq for StacktraceEntry {
             return self.debug == other.debug;
         }
         match &self.module {
+            // Frame address is unknown with 'set print address off'
+            ModuleInfo::Name(_) if self.address == 0 && other.address == 0 => {
+                self.function_name() == other.function_name()
+                    && self.debug.file_path == other.debug.file_path
+            }
             ModuleInfo::Name(_) => self.address == other.address,
             ModuleInfo::File(file1) => {
                 if let ModuleInfo::File(file2) = &other.module {
-                    if (file1.name == file2.name)
+                    (file1.name == file2.name)
                         && (self.offset().unwrap() == other.offset().unwrap())
-                    {
-                        true
-                    } else {
-                        false
-                    }
                 } else {
                     false
                 }
@@ -290,6 +973,10 @@ impl Hash for StacktraceEntry {
             return;
         }
         match &self.module {
+            ModuleInfo::Name(_) if self.address == 0 => {
+                self.function_name().hash(state);
+                self.debug.file_path.hash(state);
+            }
             ModuleInfo::Name(_) => {
                 self.address.hash(state);
             }
@@ -308,21 +995,49 @@ impl StacktraceEntry {
     ///
     /// * 'trace' - one line of stacktrace from gdb
     pub fn new(trace: &str) -> error::Result<StacktraceEntry> {
+        let arguments = Self::parse_arguments(trace);
+        // #1 0x7f1e2a3b4c5d  (/lib/x86_64-linux-gnu/libc.so.6+0x29d8f) (BuildId: 1a2b3c4d)
+        let build_id_re = Regex::new(r"\s*\(BuildId: ([0-9a-fA-F]+)\)\s*$").unwrap();
+        let build_id = build_id_re.captures(trace).map(|caps| caps[1].to_string());
+        let trace = build_id_re.replace(trace, "");
+        // Keep values collapsed by gdb as one token: 'A'<repeats200times>
+        let trace = Regex::new(REPEATS_RE)
+            .unwrap()
+            .replace_all(&trace, "<repeats${1}times>")
+            .into_owned();
         let mut vectrace = trace
             .split(' ')
             .map(|s| s.trim().to_string())
             .collect::<Vec<String>>();
-        vectrace.retain(|trace| trace != "");
-        let addr = u64::from_str_radix(
-            vectrace[1].clone().drain(2..).collect::<String>().as_str(),
-            16,
-        )
-        .unwrap_or(0);
-        let debug_line = match vectrace.last().clone() {
+        vectrace.retain(|trace| !trace.is_empty());
+        if vectrace.len() < 2 {
+            return Err(error::Error::StacktraceParse(format!(
+                "cannot parse stack frame from this string: {}",
+                trace
+            )));
+        }
+        let frame_number = vectrace[0]
+            .strip_prefix('#')
+            .and_then(|x| x.parse::<u32>().ok())
+            .unwrap_or(0);
+        // Frame address may be omitted: '#0  __GI_raise (sig=sig@entry=6) at raise.c:50'
+        let has_address = vectrace[1].starts_with("0x");
+        let addr = if has_address {
+            u64::from_str_radix(&vectrace[1][2..], 16).unwrap_or(0)
+        } else {
+            0
+        };
+        let debug_line = match vectrace.last() {
             Some(x) => x.clone().to_string(),
             None => "".to_string(),
         };
-        let first: usize = if addr == 0 { 1 } else { 3 };
+        let first: usize = if has_address { 3 } else { 1 };
+        // gdb pads frame addresses with zeros up to the target pointer size
+        let address_width = match vectrace[1].len() {
+            10 if has_address => 32,
+            18 if has_address => 64,
+            _ => 0,
+        };
 
         // In some cases we can see '#0  0xf7fcf569 in __kernel_vsyscall ()', so, pretty good
         // technical solution below
@@ -336,15 +1051,19 @@ impl StacktraceEntry {
                 String::new()
             };
 
-            return Ok(StacktraceEntry {
+            Ok(StacktraceEntry {
                 address: addr,
-                module: ModuleInfo::Name(func_with_args),
+                address_width,
+                arguments,
+                frame_number,
+                build_id,
+                module: ModuleInfo::Name(Self::known_function(func_with_args)),
                 debug: DebugInfo {
                     file_path: "".to_string(),
                     offset_in_file: None,
-                    offset_in_line: 0 as u64,
+                    offset_in_line: 0_u64,
                 },
-            });
+            })
         } else {
             let func_with_args = if first < vectrace.len() - 1 {
                 vectrace
@@ -376,17 +1095,14 @@ impl StacktraceEntry {
 
             let asan_captures = asan_base.captures(&debug_line);
             if let Some(captures) = &asan_captures {
-                let file_path = match captures
+                let file_path = captures
                     .name("file_path_1")
                     .or_else(|| captures.name("file_path_2"))
                     .or_else(|| captures.name("file_path_3"))
                     .or_else(|| captures.name("file_path_4"))
                     .or_else(|| captures.name("file_path_5"))
                     .map(|x| x.as_str().to_string())
-                {
-                    Some(x) => x,
-                    None => String::new(),
-                };
+                    .unwrap_or_default();
 
                 let mut offset_in_file = match captures
                     .name("module_offset_1")
@@ -419,24 +1135,78 @@ impl StacktraceEntry {
                 if let Some(off_in_f) = &offset_in_file {
                     return Ok(StacktraceEntry {
                         address: addr,
-                        module: ModuleInfo::Name(func_with_args),
+                        address_width,
+                        arguments,
+                        frame_number,
+                        build_id,
+                        module: ModuleInfo::Name(Self::known_function(func_with_args)),
                         debug: DebugInfo {
-                            file_path: file_path,
+                            file_path,
                             offset_in_file: Some(*off_in_f),
-                            offset_in_line: offset_in_line,
+                            offset_in_line,
                         },
                     });
                 }
             }
-            return Ok(StacktraceEntry {
+            Ok(StacktraceEntry {
                 address: addr,
-                module: ModuleInfo::Name(func_with_args),
+                address_width,
+                arguments,
+                frame_number,
+                build_id,
+                module: ModuleInfo::Name(Self::known_function(func_with_args)),
                 debug: DebugInfo {
                     file_path: debug_line,
                     offset_in_file: None,
-                    offset_in_line: 0 as u64,
+                    offset_in_line: 0_u64,
                 },
-            });
+            })
+        }
+    }
+
+    /// Returns function arguments from stack frame line.
+    ///
+    /// # Arguments
+    ///
+    /// * 'trace' - one line of stacktrace from gdb
+    fn parse_arguments(trace: &str) -> Vec<(String, String)> {
+        // #1  0x00007ffff7dd5859 in foo (a=1, s=0x4006f4 "x, y") at test.c:5
+        let prefix = Regex::new(r"^\s*#\d+\s+(?:0x[0-9a-fA-F]+ in )?").unwrap();
+        let Some(m) = prefix.find(trace) else {
+            return Vec::new();
+        };
+        let function = &trace[m.end()..];
+        // Skip template arguments in function name: 'std::map<int, int>::at (...)'
+        let mut depth = 0;
+        let mut start = None;
+        for (i, c) in function.char_indices() {
+            match c {
+                '<' => depth += 1,
+                '>' => depth -= 1,
+                '(' if depth == 0 && function[..i].ends_with(' ') => {
+                    start = Some(i + 1);
+                    break;
+                }
+                _ => {}
+            }
+        }
+        let Some(start) = start else {
+            return Vec::new();
+        };
+
+        let args = split_list(&function[start..]);
+        args.iter()
+            .filter_map(|arg| arg.split_once('='))
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect()
+    }
+
+    /// Returns empty string for unknown function in stripped binary: '?? ()'
+    fn known_function(func_with_args: String) -> String {
+        if func_with_args.starts_with("??") {
+            String::new()
+        } else {
+            func_with_args
         }
     }
 
@@ -449,37 +1219,133 @@ impl StacktraceEntry {
         self.module = ModuleInfo::File(file.clone());
     }
 
+    /// Method returns function name of the frame if it is known
+    pub fn function_name(&self) -> Option<String> {
+        let name = match &self.module {
+            ModuleInfo::Name(name) => name,
+            ModuleInfo::File(_) => return None,
+        };
+        // Function with args is stored without spaces: "__GI_raise(sig=sig@entry=6)at"
+        let name = match name.split_once('(') {
+            Some((name, _)) => name,
+            None => name
+                .strip_suffix("at")
+                .or_else(|| name.strip_suffix("from"))
+                .unwrap_or(name),
+        };
+        if name.is_empty() || name == "in" {
+            None
+        } else {
+            Some(name.to_string())
+        }
+    }
+
+    /// Method computes the offset of the address in on-disk module file (e.g. for addr2line)
+    /// using the segment that contains the address.
+    ///
+    /// # Arguments
+    ///
+    /// * 'mappings' - information about mapped files
+    pub fn file_offset(&self, mappings: &MappedFiles) -> Option<u64> {
+        let segment = mappings.find(self.address)?;
+        Some(self.address - segment.base_address + segment.offset_in_file)
+    }
+
     /// Method computes the offset between the function and the start of the file
     pub fn offset(&self) -> Option<u64> {
         match &self.module {
             ModuleInfo::Name(_) => None,
-            ModuleInfo::File(file) => {
-                Some(self.address as u64 - file.base_address + file.offset_in_file)
-            }
+            ModuleInfo::File(file) => Some(self.address - file.base_address + file.offset_in_file),
         }
     }
 }
 
-/// Struct represents the information about stack trace
-#[derive(Clone, Debug, Hash, PartialEq, Eq)]
-pub struct Stacktrace {
-    /// Vector of stack trace
-    pub strace: Vec<StacktraceEntry>,
+/// FNV-1a hasher with platform-independent integer hashing (little-endian 64-bit).
+/// Unlike `DefaultHasher`, its algorithm doesn't change between Rust releases.
+struct Fnv1aHasher(u64);
+
+impl Default for Fnv1aHasher {
+    fn default() -> Self {
+        Fnv1aHasher(0xcbf29ce484222325)
+    }
 }
 
-impl fmt::Display for Stacktrace {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        let mut entry_string = String::new();
-        for en in self.strace.iter() {
-            entry_string.push_str(&en.to_string());
-            entry_string.push_str("; \n");
+impl Hasher for Fnv1aHasher {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= u64::from(*byte);
+            self.0 = self.0.wrapping_mul(0x100000001b3);
         }
-        write!(f, "Stacktrace\n{}", entry_string)
     }
-}
 
-impl Stacktrace {
-    /// Method gets the stacktrace as a string and converts it into vector of 'StacktraceEntry' structs
+    fn write_u16(&mut self, i: u16) {
+        self.write_u64(i.into());
+    }
+
+    fn write_u32(&mut self, i: u32) {
+        self.write_u64(i.into());
+    }
+
+    fn write_u64(&mut self, i: u64) {
+        self.write(&i.to_le_bytes());
+    }
+
+    fn write_usize(&mut self, i: usize) {
+        self.write_u64(i as u64);
+    }
+
+    fn write_i16(&mut self, i: i16) {
+        self.write_i64(i.into());
+    }
+
+    fn write_i32(&mut self, i: i32) {
+        self.write_i64(i.into());
+    }
+
+    fn write_i64(&mut self, i: i64) {
+        self.write_u64(i as u64);
+    }
+
+    fn write_isize(&mut self, i: isize) {
+        self.write_i64(i as i64);
+    }
+}
+
+/// Struct represents the information about stack trace
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+pub struct Stacktrace {
+    /// Vector of stack trace
+    pub strace: Vec<StacktraceEntry>,
+}
+
+impl fmt::Display for Stacktrace {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut entry_string = String::new();
+        for en in self.strace.iter() {
+            entry_string.push_str(&en.to_string());
+            entry_string.push_str("; \n");
+        }
+        write!(f, "Stacktrace\n{}", entry_string)
+    }
+}
+
+/// `CheckedStacktrace` struct represents stack trace with its reliability flags.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CheckedStacktrace {
+    /// Parsed stack trace
+    pub stacktrace: Stacktrace,
+    /// Gdb stopped unwinding before the outermost frame
+    pub truncated: bool,
+    /// Gdb reported corrupt stack (Backtrace stopped: ... (corrupt stack?))
+    pub corrupt: bool,
+}
+
+impl Stacktrace {
+    /// Method gets the stacktrace as a string and converts it into vector of 'StacktraceEntry' structs
     ///
     /// # Arguments
     ///
@@ -490,13 +1356,15 @@ impl Stacktrace {
     /// The return value is a vector of  'StacktraceEntry' structs
     pub fn from_gdb(trace: &str) -> error::Result<Stacktrace> {
         let mut some = Vec::<StacktraceEntry>::new();
-        let mut entries = trace
-            .split('\n')
+        let mut entries = remove_pagination(&remove_notices(trace))
+            .lines()
             .map(|s| s.trim().to_string())
             .collect::<Vec<String>>();
-        entries.retain(|trace| trace != "");
+        entries.retain(|trace| {
+            !trace.is_empty() && !is_backtrace_sentinel(trace) && !is_source_line(trace)
+        });
 
-        if entries.len() < 1 {
+        if entries.is_empty() {
             return Err(error::Error::StacktraceParse(
                 format!("cannot get stack trace from this string: {}", trace).to_string(),
             ));
@@ -508,6 +1376,270 @@ impl Stacktrace {
         Ok(Stacktrace { strace: some })
     }
 
+    /// Method parses stack trace and checks if it is reliable
+    ///
+    /// # Arguments
+    ///
+    /// * 'trace' - stacktrace from gdb
+    pub fn from_gdb_checked(trace: &str) -> error::Result<CheckedStacktrace> {
+        let stacktrace = Stacktrace::from_gdb(trace)?;
+        let sentinels = trace
+            .lines()
+            .map(|x| x.trim())
+            .filter(|x| is_backtrace_sentinel(x))
+            .collect::<Vec<&str>>();
+        Ok(CheckedStacktrace {
+            stacktrace,
+            truncated: !sentinels.is_empty(),
+            corrupt: sentinels.iter().any(|x| x.contains("corrupt stack")),
+        })
+    }
+
+    /// Method returns crash hash of stack trace. Equal stack traces (see
+    /// `StacktraceEntry` equality) have equal hashes. Hash is stable between runs
+    /// and platforms, so it may be stored for deduplication.
+    pub fn crash_hash(&self) -> u64 {
+        let mut hasher = Fnv1aHasher::default();
+        self.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    /// Method returns similarity of stack traces from 0.0 to 1.0: doubled length of
+    /// the longest common subsequence of frames (`StacktraceEntry` equality) divided by
+    /// total number of frames. Two empty stack traces are equal.
+    ///
+    /// # Arguments
+    ///
+    /// * 'other' - stack trace to compare with
+    pub fn similarity(&self, other: &Stacktrace) -> f64 {
+        let (a, b) = (&self.strace, &other.strace);
+        if a.is_empty() && b.is_empty() {
+            return 1.0;
+        }
+        // lcs[j] is LCS length of processed prefix of `a` and b[..j]
+        let mut lcs = vec![0usize; b.len() + 1];
+        for x in a.iter() {
+            let mut diagonal = 0;
+            for (j, y) in b.iter().enumerate() {
+                let up = lcs[j + 1];
+                lcs[j + 1] = if x == y { diagonal + 1 } else { up.max(lcs[j]) };
+                diagonal = up;
+            }
+        }
+        2.0 * lcs[b.len()] as f64 / (a.len() + b.len()) as f64
+    }
+
+    /// Method returns stack trace in normalized format, one frame per line:
+    /// `#N 0xADDR in func at file:line:col`. Frame numbers are taken from gdb output,
+    /// so they are kept for trimmed stack traces.
+    pub fn to_pretty_string(&self) -> String {
+        let mut lines = Vec::new();
+        for (i, entry) in self.strace.iter().enumerate() {
+            // Frame number isn't known (0) for frames created not from gdb output
+            let number = if entry.frame_number == 0 {
+                i as u32
+            } else {
+                entry.frame_number
+            };
+            let mut line = format!("#{}", number);
+            if entry.address != 0 {
+                line.push_str(&format!(
+                    " 0x{:0w$x}",
+                    entry.address,
+                    w = (entry.address_width / 4) as usize
+                ));
+            }
+            if let Some(function) = entry.function_name() {
+                line.push_str(&format!(" in {}", function));
+            }
+            if let ModuleInfo::File(file) = &entry.module {
+                line.push_str(&format!(
+                    " ({}+0x{:x})",
+                    file.name,
+                    entry.offset().unwrap_or_default()
+                ));
+            }
+            if let Some(file_line) = entry.debug.offset_in_file {
+                line.push_str(&format!(" at {}:{}", entry.debug.file_path, file_line));
+                if entry.debug.offset_in_line != 0 {
+                    line.push_str(&format!(":{}", entry.debug.offset_in_line));
+                }
+            } else if !entry.debug.file_path.is_empty() {
+                line.push_str(&format!(" from {}", entry.debug.file_path));
+            }
+            lines.push(line);
+        }
+        lines.join("\n")
+    }
+
+    /// Method parses full stacktrace from gdb (bt full) into frames with their local variables
+    ///
+    /// # Arguments
+    ///
+    /// * 'trace' - full stacktrace from gdb
+    ///
+    /// # Return value
+    ///
+    /// The return value is a vector of 'StacktraceEntry' structs with local variables
+    pub fn from_gdb_full(
+        trace: &str,
+    ) -> error::Result<Vec<(StacktraceEntry, BTreeMap<String, String>)>> {
+        let mut frames: Vec<(StacktraceEntry, BTreeMap<String, String>)> = Vec::new();
+        // Local which value is printed on several lines (set print pretty on)
+        let mut unfinished: Option<String> = None;
+        let mut depth = 0;
+        for line in trace.lines() {
+            if let (Some(name), Some((_, locals))) = (unfinished.clone(), frames.last_mut()) {
+                // Join value lines into one-line form: "{a = 1, b = {c = 2}}"
+                let value = locals.entry(name).or_default();
+                if value.ends_with(',') {
+                    value.push(' ');
+                }
+                value.push_str(line.trim());
+                depth += brace_depth(line);
+                if depth <= 0 {
+                    unfinished = None;
+                }
+                continue;
+            }
+            if line.trim_start().starts_with('#') {
+                frames.push((StacktraceEntry::new(line.trim())?, BTreeMap::new()));
+                continue;
+            }
+            // Locals are indented under the frame: "        ret = <optimized out>"
+            if !line.starts_with(char::is_whitespace) {
+                continue;
+            }
+            if let (Some((_, locals)), Some((name, value))) =
+                (frames.last_mut(), line.trim().split_once(" = "))
+            {
+                locals.insert(name.to_string(), value.to_string());
+                depth = brace_depth(value);
+                if depth > 0 {
+                    unfinished = Some(name.to_string());
+                }
+            }
+        }
+
+        if frames.is_empty() {
+            return Err(error::Error::StacktraceParse(format!(
+                "cannot get stack trace from this string: {}",
+                trace
+            )));
+        }
+        Ok(frames)
+    }
+
+    /// Method parses only the first frame (#0) of the stacktrace from gdb.
+    ///
+    /// # Arguments
+    ///
+    /// * 'trace' - stacktrace from gdb
+    pub fn top_frame(trace: &str) -> error::Result<Option<StacktraceEntry>> {
+        match trace
+            .lines()
+            .map(|line| line.trim())
+            .find(|line| line.starts_with("#0 "))
+        {
+            Some(line) => Ok(Some(StacktraceEntry::new(line)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Method returns file name of the crashing module (main executable or library)
+    /// after `update_modules`. Inlined frames without address are skipped.
+    /// Returns None if crash address isn't in any mapped file.
+    pub fn crashing_module(&self) -> Option<String> {
+        let frame = self.strace.iter().find(|x| x.address != 0)?;
+        match &frame.module {
+            ModuleInfo::File(file) if !file.name.is_empty() => Some(
+                Path::new(&file.name)
+                    .file_name()
+                    .map(|x| x.to_string_lossy().to_string())
+                    .unwrap_or_else(|| file.name.clone()),
+            ),
+            _ => None,
+        }
+    }
+
+    /// Method removes runtime frames (`RUNTIME_FUNCTIONS`) above the crash site.
+    /// Call it before `update_modules`, which replaces function names with modules.
+    pub fn trim_to_signal(&mut self) {
+        self.trim_frames(RUNTIME_FUNCTIONS);
+    }
+
+    /// Method removes leading frames with functions from denylist until the first
+    /// application frame. Stack trace isn't changed if all frames are in denylist.
+    ///
+    /// # Arguments
+    ///
+    /// * 'denylist' - function names, names ending with `*` are prefixes
+    pub fn trim_frames(&mut self, denylist: &[&str]) {
+        if let Some(pos) = self
+            .strace
+            .iter()
+            .position(|x| !function_matches(x, denylist))
+        {
+            self.strace.drain(..pos);
+        }
+    }
+
+    /// Method returns the first user frame below Rust panic machinery
+    /// (`RUST_PANIC_FUNCTIONS`) for both `panic=abort` and unwinding builds.
+    /// Returns None if stack trace has no panic frames.
+    /// Call it before `update_modules`, which replaces function names with modules.
+    pub fn rust_panic_frame(&self) -> Option<&StacktraceEntry> {
+        let start = self
+            .strace
+            .iter()
+            .position(|x| function_matches(x, RUST_PANIC_FUNCTIONS))?;
+        self.strace[start..]
+            .iter()
+            .find(|x| !function_matches(x, RUST_PANIC_FUNCTIONS))
+    }
+
+    /// Method returns the innermost frame from application module. Module is the
+    /// mapped file name after `update_modules`, otherwise the source or library
+    /// path from gdb output. Frames without module are skipped.
+    ///
+    /// # Arguments
+    ///
+    /// * 'module_denylist' - patterns of system and runtime modules (e.g., `^/lib/`)
+    pub fn app_frame(&self, module_denylist: &[Regex]) -> Option<&StacktraceEntry> {
+        self.strace.iter().find(|entry| {
+            let module = match &entry.module {
+                ModuleInfo::File(file) => &file.name,
+                ModuleInfo::Name(_) => &entry.debug.file_path,
+            };
+            !module.is_empty() && !module_denylist.iter().any(|re| re.is_match(module))
+        })
+    }
+
+    /// Method merges ASan and gdb stack traces of the same crash. Gdb frames are kept,
+    /// frames without debug information get it from equal ASan frames.
+    ///
+    /// # Arguments
+    ///
+    /// * 'asan' - stack trace from ASan report
+    ///
+    /// * 'gdb' - stack trace from gdb
+    pub fn merge(asan: &Stacktrace, gdb: &Stacktrace) -> Stacktrace {
+        let mut merged = gdb.clone();
+        for entry in merged.strace.iter_mut() {
+            if entry.debug.offset_in_file.is_some() {
+                continue;
+            }
+            if let Some(asan_entry) = asan
+                .strace
+                .iter()
+                .find(|x| x.debug.offset_in_file.is_some() && *entry == **x)
+            {
+                entry.debug = asan_entry.debug.clone();
+            }
+        }
+        merged
+    }
+
     /// Method updates information about function modules.
     ///
     /// # Arguments
@@ -520,85 +1652,1853 @@ impl Stacktrace {
             }
         });
     }
+
+    /// Method fills debug information for frames without it using DWARF info
+    /// from module files (`symbolize` feature). Frames which modules can't be
+    /// read are left unchanged.
+    ///
+    /// # Arguments
+    ///
+    /// * 'mappings' - information about mapped files
+    #[cfg(feature = "symbolize")]
+    pub fn symbolize(&mut self, mappings: &MappedFiles) {
+        use object::{Object, ObjectSegment};
+
+        // Module loader and (file offset, size, address) of its segments
+        type Module = (addr2line::Loader, Vec<(u64, u64, u64)>);
+        let mut modules: HashMap<String, Option<Module>> = HashMap::new();
+        for entry in self.strace.iter_mut() {
+            if entry.debug.offset_in_file.is_some() {
+                continue;
+            }
+            let (module, file_offset) =
+                match (mappings.find(entry.address), entry.file_offset(mappings)) {
+                    (Some(module), Some(offset)) if !module.name.is_empty() => (module, offset),
+                    _ => continue,
+                };
+            let loaded = modules.entry(module.name.clone()).or_insert_with(|| {
+                let data = fs::read(&module.name).ok()?;
+                let segments = object::File::parse(&*data)
+                    .ok()?
+                    .segments()
+                    .map(|s| (s.file_range().0, s.file_range().1, s.address()))
+                    .collect();
+                Some((addr2line::Loader::new(&module.name).ok()?, segments))
+            });
+            let (loader, segments) = match loaded {
+                Some(loaded) => loaded,
+                None => continue,
+            };
+            // Translate file offset to virtual address in module
+            let address = match segments
+                .iter()
+                .find(|(offset, size, _)| *offset <= file_offset && file_offset < offset + size)
+            {
+                Some((offset, _, address)) => address + file_offset - offset,
+                None => continue,
+            };
+            if let Ok(Some(location)) = loader.find_location(address) {
+                if let (Some(file), Some(line)) = (location.file, location.line) {
+                    entry.debug = DebugInfo {
+                        file_path: file.to_string(),
+                        offset_in_file: Some(line as u64),
+                        offset_in_line: location.column.unwrap_or_default() as u64,
+                    };
+                }
+            }
+        }
+    }
+
+    /// Method returns location of the crash. For signals raised by the program
+    /// itself (SIGABRT) runtime frames (`RUNTIME_FUNCTIONS`) are skipped, so location
+    /// points to the code that called `abort`. Call it before `update_modules`,
+    /// which replaces function names with modules. If the frame has no debug
+    /// information, module path and offset in module file are returned.
+    /// Returns `None` if stack trace is empty.
+    ///
+    /// # Arguments
+    ///
+    /// * 'stop_reason' - signal that stopped the program
+    ///
+    /// * 'mappings' - information about mapped files
+    pub fn crash_location(
+        &self,
+        stop_reason: Option<&StopReason>,
+        mappings: &MappedFiles,
+    ) -> Option<CrashLocation> {
+        let mut sttr = self.clone();
+        if stop_reason.is_some_and(|x| x.signal == "SIGABRT") {
+            sttr.trim_to_signal();
+        }
+        let frame = sttr.strace.first()?;
+        if frame.debug.offset_in_file.is_some() {
+            return Some(CrashLocation::Source(frame.debug.clone()));
+        }
+        let file = match &frame.module {
+            ModuleInfo::File(file) => Some(file.clone()),
+            ModuleInfo::Name(_) => mappings.find(frame.address),
+        };
+        match file {
+            Some(file) if !file.name.is_empty() => Some(CrashLocation::Module {
+                offset: frame.address - file.base_address + file.offset_in_file,
+                path: file.name,
+            }),
+            _ => Some(CrashLocation::Address {
+                address: frame.address,
+                function: frame.function_name(),
+            }),
+        }
+    }
 }
 
 pub mod error;
-/// Type of `gdb` execution: Remote attach to process, local run with args, core.
-#[derive(Debug, Clone)]
-pub enum ExecType<'a> {
-    /// Run target program via `gdb` (--args) option.
-    Local(&'a [&'a str]),
-    /// Run target program built with address sanitizer via `gdb` (--args) option.
-    ASan(&'a [&'a str]),
-    /// Attach to process via `gdb` (-p) option.
-    Remote(&'a str),
-    /// Run target via `gdb` with coredump.
-    Core { target: &'a str, core: &'a str },
+
+/// Regex for values collapsed by gdb: `'A' <repeats 200 times>`.
+const REPEATS_RE: &str = r" <repeats (\d+) times>";
+
+/// Function removes gdb pagination prompts from output:
+/// `--Type <RET> for more, q to quit, c to continue without paging--`
+fn remove_pagination(output: &str) -> String {
+    let re = Regex::new(r"-{2,3}Type <(?:RET|return)>.*?-{2,3}").unwrap();
+    re.replace_all(output, "").to_string()
 }
 
-/// Struct contains information about arguments for `gdb` to run.
-#[derive(Debug)]
-pub struct GdbCommand<'a> {
-    /// Gdb execution type.
-    exec_type: ExecType<'a>,
-    /// Execution parameters (-ex).
-    args: Vec<&'a str>,
-    /// Stdin file
-    stdin: Option<&'a PathBuf>,
+/// Function removes gdb thread and inferior notices that may appear anywhere in output:
+/// `[New Thread 0x7ffff7d8a640 (LWP 42)]`, `[Inferior 1 (process 42) exited normally]`
+fn remove_notices(output: &str) -> String {
+    let re = Regex::new(
+        r"(?m)^\[(?:New Thread |New LWP |Thread 0x[0-9a-fA-F]+ \(LWP \d+\) exited|Switching to |Inferior \d+ \(|Thread debugging using |New inferior |Detaching after v?fork )[^\n]*\]\r?(?:\n|$)",
+    )
+    .unwrap();
+    re.replace_all(output, "").to_string()
 }
 
-impl<'a> GdbCommand<'a> {
-    /// Construct `GdbCommand` from given ExecType.
-    /// # Arguments
-    ///
-    /// * `type` - execution type to run gdb.
-    pub fn new(exec_type: &'a ExecType) -> GdbCommand<'a> {
-        GdbCommand {
-            exec_type: exec_type.clone(),
-            args: Vec::new(),
-            stdin: None,
+/// Function splits gdb values list (`{1, 0 <repeats 15 times>, 2}`) into separate values.
+/// Values collapsed by gdb (`<repeats N times>`) are expanded.
+///
+/// # Arguments
+///
+/// * 'values' - values list from gdb
+pub fn expand_repeats(values: &str) -> error::Result<Vec<String>> {
+    let values = values.trim();
+    let values = values
+        .strip_prefix('{')
+        .and_then(|v| v.strip_suffix('}'))
+        .unwrap_or(values);
+
+    let mut items = split_list(values);
+    items.retain(|item| !item.is_empty());
+
+    let re = Regex::new(&format!("^(.*){}$", REPEATS_RE)).unwrap();
+    let mut result = Vec::new();
+    for item in items {
+        if let Some(caps) = re.captures(item) {
+            let count = caps[2].parse::<usize>()?;
+            result.resize(result.len() + count, caps[1].to_string());
+        } else {
+            result.push(item.to_string());
+        }
+    }
+    Ok(result)
+}
+
+/// Function splits list of gdb values, function arguments or instruction operands
+/// by commas outside of quotes and brackets into trimmed items. Splitting stops at
+/// unmatched closing bracket, e.g. at the end of function arguments list.
+fn split_list(list: &str) -> Vec<&str> {
+    let mut items = Vec::new();
+    let mut quote: Option<char> = None;
+    let mut escaped = false;
+    let mut depth = 0;
+    let mut start = 0;
+    for (i, c) in list.char_indices() {
+        match (quote, c) {
+            (Some(_), _) if escaped => escaped = false,
+            (Some(_), '\\') => escaped = true,
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '"') | (None, '\'') => quote = Some(c),
+            (None, '{') | (None, '(') | (None, '[') | (None, '<') => depth += 1,
+            (None, '}') | (None, ')') | (None, ']') | (None, '>') if depth == 0 => {
+                items.push(list[start..i].trim());
+                return items;
+            }
+            (None, '}') | (None, ')') | (None, ']') | (None, '>') => depth -= 1,
+            (None, ',') if depth == 0 => {
+                items.push(list[start..i].trim());
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    let last = list[start..].trim();
+    if !last.is_empty() || !items.is_empty() {
+        items.push(last);
+    }
+    items
+}
+
+/// Gdb messages that are printed instead of command result.
+const GDB_ERRORS: &[&str] = &[
+    "No stack.",
+    "No symbol table is loaded",
+    "No registers.",
+    "The program has no registers now.",
+    "No frame selected.",
+];
+
+/// Characters that can't be passed in single-quoted stdin redirection path
+/// (gdb runs program via shell).
+const STDIN_FORBIDDEN: &str = "\n\r'";
+
+/// Runtime functions that may be above the crash site in stack trace
+/// (signal raising, sanitizers, fortify checks). Names ending with `*` are prefixes.
+pub const RUNTIME_FUNCTIONS: &[&str] = &[
+    "__asan_*",
+    "__sanitizer_*",
+    "__ubsan_*",
+    "__interceptor_*",
+    "__pthread_kill*",
+    "pthread_kill",
+    "raise",
+    "__GI_raise",
+    "gsignal",
+    "abort",
+    "__GI_abort",
+    "__libc_message",
+    "__fortify_fail",
+    "__chk_fail",
+    "__stack_chk_fail",
+    "__assert_fail",
+    "__assert_fail_base",
+    "__GI___assert_fail",
+    "__kernel_vsyscall",
+];
+
+/// Rust panic functions that are between abort and the panicking user frame
+/// in stack trace. Names ending with `*` are prefixes.
+pub const RUST_PANIC_FUNCTIONS: &[&str] = &[
+    "rust_panic*",
+    "rust_begin_unwind",
+    "__rustc::rust_begin_unwind",
+    "__rust_start_panic",
+    "std::panicking::*",
+    "core::panicking::*",
+    "std::panic::panic_any*",
+    "std::sys::backtrace::__rust_end_short_backtrace*",
+    "std::sys_common::backtrace::__rust_end_short_backtrace*",
+    "core::result::unwrap_failed",
+    "core::result::Result<*",
+    "core::option::unwrap_failed",
+    "core::option::expect_failed",
+    "core::option::Option<*",
+    "core::slice::index::slice_*",
+    "core::cell::panic_already_*",
+];
+
+/// Functions that abort the program after detecting memory corruption.
+/// Names ending with `*` are prefixes.
+pub const CORRUPTION_CHECKS: &[&str] = &[
+    "__stack_chk_fail",
+    "__fortify_fail",
+    "__chk_fail",
+    "malloc_printerr",
+    "__malloc_assert",
+];
+
+/// Returns true if frame function matches any name from the list.
+/// Names ending with `*` are prefixes.
+fn function_matches(entry: &StacktraceEntry, names: &[&str]) -> bool {
+    let Some(function) = entry.function_name() else {
+        return false;
+    };
+    names.iter().any(|name| match name.strip_suffix('*') {
+        Some(prefix) => function.starts_with(prefix),
+        None => function == *name,
+    })
+}
+
+/// Gdb version (major, minor), cached after the first `gdb --version` call.
+/// `None` if the version can't be determined.
+static GDB_VERSION: OnceLock<Option<(u32, u32)>> = OnceLock::new();
+
+/// Command to get sections of all objfiles (gdb 11+ syntax).
+const ALL_SECTIONS_COMMAND: &str = "maintenance info sections -all-objects";
+
+/// Command to enable logging (gdb 12+ syntax).
+const LOGGING_COMMAND: &str = "set logging enabled on";
+
+/// Returns regex that matches section line of `maintenance info sections`.
+fn section_regex() -> Regex {
+    // [0]      0x00000318->0x00000334 at 0x00000318: .interp ALLOC LOAD READONLY DATA
+    Regex::new(
+        r"^\s*\[\s*\d+\]\s+0x([0-9a-fA-F]+)->0x([0-9a-fA-F]+) at 0x([0-9a-fA-F]+): (\S+)(.*)$",
+    )
+    .unwrap()
+}
+
+/// Function returns error if path contains any of forbidden characters.
+///
+/// # Arguments
+///
+/// * 'path' - path to check
+///
+/// * 'forbidden' - forbidden characters
+fn check_path(path: &Path, forbidden: &str) -> error::Result<()> {
+    let path = path.to_string_lossy();
+    if path.contains(|c| forbidden.contains(c)) {
+        return Err(error::Error::InvalidPath(path.to_string()));
+    }
+    Ok(())
+}
+
+/// Function returns error if file doesn't start with ELF header of core file
+/// (e_type is ET_CORE).
+///
+/// # Arguments
+///
+/// * 'path' - path to core file
+fn check_core(path: &str) -> error::Result<()> {
+    // e_ident (16 bytes) and e_type (2 bytes)
+    let mut header = Vec::new();
+    fs::File::open(path)?.take(18).read_to_end(&mut header)?;
+    if header.len() < 18 {
+        return Err(error::Error::InvalidCore(format!(
+            "{}: file is truncated ({} bytes)",
+            path,
+            header.len()
+        )));
+    }
+    if header[..4] != *b"\x7fELF" {
+        return Err(error::Error::InvalidCore(format!(
+            "{}: not an ELF file",
+            path
+        )));
+    }
+    // EI_DATA: 1 - little endian, 2 - big endian
+    let e_type = if header[5] == 2 {
+        u16::from_be_bytes([header[16], header[17]])
+    } else {
+        u16::from_le_bytes([header[16], header[17]])
+    };
+    // ET_CORE
+    if e_type != 4 {
+        return Err(error::Error::InvalidCore(format!(
+            "{}: ELF type {} is not a core",
+            path, e_type
+        )));
+    }
+    Ok(())
+}
+
+/// Function checks if line is a gdb note about unwinding end, not a frame.
+fn is_backtrace_sentinel(line: &str) -> bool {
+    // Backtrace stopped: previous frame inner to this frame (corrupt stack?)
+    // (More stack frames follow...)
+    line.starts_with("Backtrace stopped:") || line.starts_with("(More stack frames follow")
+}
+
+/// Function checks if line is a source line printed after frame
+/// (`set print frame-info source-and-location`), not a frame.
+fn is_source_line(line: &str) -> bool {
+    // 10	  abort ();
+    let digits = line.bytes().take_while(|x| x.is_ascii_digit()).count();
+    digits > 0 && line.as_bytes().get(digits) == Some(&b'\t')
+}
+
+/// Function returns difference between numbers of opening and closing braces
+/// outside of quotes in gdb output line.
+fn brace_depth(line: &str) -> i32 {
+    let mut depth = 0;
+    let mut quote: Option<char> = None;
+    let mut escaped = false;
+    for c in line.chars() {
+        match (quote, c) {
+            (Some(_), _) if escaped => escaped = false,
+            (Some(_), '\\') => escaped = true,
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '"') | (None, '\'') => quote = Some(c),
+            (None, '{') => depth += 1,
+            (None, '}') => depth -= 1,
+            _ => {}
+        }
+    }
+    depth
+}
+
+/// Function returns `set args` gdb command for program arguments. Gdb passes
+/// arguments set this way to shell, so they are quoted by shell rules.
+/// It is an alternative to `--args` when command line is reconstructed as string.
+/// Arguments must not contain newlines, gdb commands are one line.
+///
+/// # Arguments
+///
+/// * 'args' - program arguments (without program path)
+pub fn set_args_command(args: &[&str]) -> String {
+    let mut cmd = "set args".to_string();
+    for arg in args {
+        cmd.push(' ');
+        if !arg.is_empty()
+            && arg
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || "-_./=:,+@%".contains(c))
+        {
+            cmd.push_str(arg);
+        } else {
+            cmd.push_str(&format!("'{}'", arg.replace('\'', "'\\''")));
+        }
+    }
+    cmd
+}
+
+/// Function returns exit code of the program if it exited (not crashed).
+///
+/// # Arguments
+///
+/// * 'output' - gdb output (`raw`), which contains program run
+pub fn program_exited(output: &str) -> Option<i32> {
+    // [Inferior 1 (process 42) exited with code 01]
+    let re =
+        Regex::new(r"\[Inferior \d+ \([^)]*\) exited (?:normally|with code ([0-7]+))\]").unwrap();
+    let caps = re.captures(output)?;
+    match caps.get(1) {
+        // gdb prints exit code in octal
+        Some(code) => i32::from_str_radix(code.as_str(), 8).ok(),
+        None => Some(0),
+    }
+}
+
+/// Function returns command line of the process that generated core.
+/// Kernel stores only the first 80 bytes of command line in core.
+///
+/// # Arguments
+///
+/// * 'output' - gdb output (`raw`) with core loading
+pub fn core_cmdline(output: &str) -> Option<String> {
+    // Core was generated by `./prog --flag input'.
+    let re = Regex::new(r"(?m)^Core was generated by [`'](.*)'\.\r?$").unwrap();
+    let caps = re.captures(output)?;
+    Some(caps[1].to_string())
+}
+
+/// Function returns command line arguments of the process that generated core.
+/// Arguments are split by whitespaces since quoting isn't preserved in core.
+///
+/// # Arguments
+///
+/// * 'output' - gdb output (`raw`) with core loading
+pub fn core_args(output: &str) -> Option<Vec<String>> {
+    core_cmdline(output).map(|cmdline| cmdline.split_whitespace().map(|x| x.to_string()).collect())
+}
+
+/// Function returns faulting memory address from `siginfo` command output.
+/// Returns None if `$_siginfo` is unavailable (no signal or no process).
+///
+/// # Arguments
+///
+/// * 'output' - gdb output of `siginfo` command
+pub fn fault_address(output: &str) -> Option<u64> {
+    // $1 = (void *) 0x0
+    // $1 = void
+    let re = Regex::new(r"^\$\d+\s*=\s*\(void \*\) 0x([0-9a-fA-F]+)").unwrap();
+    let caps = re.captures(output.trim())?;
+    u64::from_str_radix(&caps[1], 16).ok()
+}
+
+/// Function runs gdb once for several cores of the same target and groups
+/// core paths by crash hash of their stack traces. If some core can't be
+/// analyzed, error is returned instead of grouping the rest.
+///
+/// # Arguments
+///
+/// * 'cores' - paths to core files
+///
+/// * 'target' - path to target binary
+pub fn dedup_cores(cores: &[&str], target: &str) -> error::Result<BTreeMap<u64, Vec<String>>> {
+    let mut groups: BTreeMap<u64, Vec<String>> = BTreeMap::new();
+    if cores.is_empty() {
+        return Ok(groups);
+    }
+    let results = GdbCommand::new(&ExecType::Core {
+        target,
+        core: cores[0],
+    })
+    .bt()
+    .run_batch(cores)?;
+    // run_batch fails if some core isn't analyzed, so there is a result for each core
+    for (core, result) in cores.iter().zip(results.iter()) {
+        let hash = Stacktrace::from_gdb(&result[0])?.crash_hash();
+        groups.entry(hash).or_default().push(core.to_string());
+    }
+    Ok(groups)
+}
+
+/// Function returns build-id (hex) for each module from `build_ids` command output.
+/// Modules without build-id are skipped.
+///
+/// # Arguments
+///
+/// * 'output' - gdb output of `build_ids` command
+pub fn build_ids(output: &str) -> BTreeMap<String, String> {
+    // /lib/x86_64-linux-gnu/libc.so.6\t490fef8403240c91833978d494d39e537409b92e
+    output
+        .lines()
+        .filter_map(|line| line.rsplit_once('\t'))
+        .filter(|(_, id)| !id.is_empty() && id.chars().all(|c| c.is_ascii_hexdigit()))
+        .map(|(module, id)| (module.to_string(), id.to_string()))
+        .collect()
+}
+
+/// Function returns catchpoint hit line if program stopped at catchpoint.
+///
+/// # Arguments
+///
+/// * 'output' - gdb output (`raw`), which contains program run or continue
+pub fn catchpoint_hit(output: &str) -> Option<String> {
+    // Catchpoint 1 (call to syscall write), 0x00007ffff7e9a297 in write () from /lib/libc.so.6
+    // Catchpoint 1 (exception thrown), 0x00007ffff7e4a672 in __cxa_throw () from /lib/libstdc++.so.6
+    let re = Regex::new(r"(?m)^Catchpoint \d+ \([^)]*\), .*$").unwrap();
+    re.find(output).map(|x| x.as_str().trim_end().to_string())
+}
+
+/// Function returns bytes from `x/Nxb` command output (e.g., `faulting_bytes`).
+/// Only bytes read before memory access error are returned, so the result is
+/// empty if memory at the address isn't readable.
+///
+/// # Arguments
+///
+/// * 'output' - gdb output of `x/Nxb` command
+pub fn memory_bytes(output: &str) -> Vec<u8> {
+    // 0x555555555169 <main+4>:	0xc7	0x00	0x00	0x00
+    // Cannot access memory at address 0x0
+    let mut bytes = Vec::new();
+    for line in output.lines() {
+        let Some((address, values)) = line.split_once(':') else {
+            break;
+        };
+        if !address.trim_start().starts_with("0x") {
+            break;
+        }
+        for value in values.split_whitespace() {
+            match value
+                .strip_prefix("0x")
+                .and_then(|x| u8::from_str_radix(x, 16).ok())
+            {
+                Some(byte) => bytes.push(byte),
+                None => return bytes,
+            }
+        }
+    }
+    bytes
+}
+
+/// Function returns target architecture from `show architecture` command output.
+/// For automatically detected architecture the current value is returned.
+///
+/// # Arguments
+///
+/// * 'output' - gdb output of `show architecture` command
+pub fn architecture(output: &str) -> Option<String> {
+    // The target architecture is set to "auto" (currently "i386:x86-64").
+    // The target architecture is set automatically (currently i386:x86-64)
+    // The target architecture is set to "aarch64".
+    // The target architecture is assumed to be i386:x86-64
+    let current = Regex::new(r#"\(currently "?([^")]+)"?\)"#).unwrap();
+    let set = Regex::new(r#"set to "([^"]+)"|assumed to be (\S+)"#).unwrap();
+    let caps = current.captures(output).or_else(|| set.captures(output))?;
+    caps.iter()
+        .skip(1)
+        .flatten()
+        .next()
+        .map(|x| x.as_str().to_string())
+}
+
+/// Function returns values of thread-local expression for each thread
+/// (thread number -> value) from `print_tls` command output.
+/// Returns `TlsUnavailable` error if gdb cannot find thread-local storage.
+///
+/// # Arguments
+///
+/// * 'output' - gdb output of `print_tls` command
+pub fn tls_values(output: &str) -> error::Result<BTreeMap<u32, String>> {
+    // Thread 2 (Thread 0x7ffff7d86640 (LWP 1235) "test"):
+    // $1 = 0
+    //
+    // Cannot find thread-local storage for Thread 0x7ffff7d87740 (LWP 1234), executable file /tmp/test:
+    // Cannot find thread-local variables on this target
+    if let Some(line) = output
+        .lines()
+        .find(|x| x.starts_with("Cannot find thread-local"))
+    {
+        return Err(error::Error::TlsUnavailable(
+            line.trim_end_matches(':').to_string(),
+        ));
+    }
+    let thread = Regex::new(r"^Thread (\d+) ").unwrap();
+    let value = Regex::new(r"^\$\d+ = (.*)$").unwrap();
+    let mut values = BTreeMap::new();
+    let mut current = None;
+    for line in output.lines() {
+        if let Some(caps) = thread.captures(line) {
+            current = Some(caps[1].parse::<u32>()?);
+        } else if let (Some(num), Some(caps)) = (current, value.captures(line)) {
+            values.insert(num, caps[1].to_string());
+            current = None;
+        }
+    }
+    Ok(values)
+}
+
+/// Function returns symbol address from `address_of` (`info address`) command output.
+/// Returns None if symbol isn't found or has no static address (e.g., local variable).
+///
+/// # Arguments
+///
+/// * 'output' - gdb output of `info address` command
+pub fn symbol_address(output: &str) -> Option<u64> {
+    // Symbol "main" is a function at address 0x555555555149.
+    // Symbol "printf" is at 0x7ffff7e0a6f0 in a file compiled without debugging.
+    // Symbol "x" is a variable at frame base reg $rbp offset 16+-20.
+    let re = Regex::new(r#"^Symbol "[^"]*" is .*?\bat (?:address )?0x([0-9a-fA-F]+)"#).unwrap();
+    let caps = re.captures(output.trim())?;
+    u64::from_str_radix(&caps[1], 16).ok()
+}
+
+/// Function returns environment variables from `env` (`show environment`) command output.
+/// Each line is split on the first `=`, so values may contain `=`. Line that doesn't
+/// start with variable name is a continuation of multi-line value.
+///
+/// # Arguments
+///
+/// * 'output' - gdb output of `show environment` command
+pub fn parse_environment(output: &str) -> BTreeMap<String, String> {
+    // LANG=C.UTF-8
+    // LS_COLORS=rs=0:di=01;34:ln=01;36
+    let mut env = BTreeMap::new();
+    let mut last: Option<String> = None;
+    for line in output.lines() {
+        match line.split_once('=') {
+            Some((key, value)) if !key.is_empty() && !key.contains(char::is_whitespace) => {
+                env.insert(key.to_string(), value.to_string());
+                last = Some(key.to_string());
+            }
+            _ => {
+                if let Some(value) = last.as_ref().and_then(|key| env.get_mut(key)) {
+                    value.push('\n');
+                    value.push_str(line);
+                }
+            }
+        }
+    }
+    env
+}
+
+/// Function returns path of core file from `generate_core` command output.
+/// Returns `CommandFailed` error with gdb message if core isn't saved
+/// (e.g., permission denied).
+///
+/// # Arguments
+///
+/// * 'output' - gdb output of `generate_core` command
+pub fn saved_core(output: &str) -> error::Result<PathBuf> {
+    // Saved corefile /tmp/core.1234
+    // Failed to open '/root/core.1234' for output.
+    if let Some(path) = output
+        .lines()
+        .find_map(|x| x.strip_prefix("Saved corefile "))
+    {
+        return Ok(PathBuf::from(path.trim_end()));
+    }
+    Err(error::Error::CommandFailed {
+        command: "generate-core-file".to_string(),
+        message: output.lines().next().unwrap_or_default().to_string(),
+    })
+}
+
+/// Function splits `apply_all_threads` command output by `Thread N` headers and
+/// returns (thread id, output) pairs in gdb order. Non-empty output without
+/// thread headers is an error.
+///
+/// # Arguments
+///
+/// * 'output' - gdb output of `thread apply all` command
+pub fn split_threads(output: &str) -> error::Result<Vec<(String, String)>> {
+    // Thread 2 (Thread 0x7ffff7d86640 (LWP 1235) "test"):
+    let re = Regex::new(r"^Thread ([\d.]+) \(.*\):$").unwrap();
+    let threads = split_by_headers(output, |line| {
+        re.captures(line).map(|caps| caps[1].to_string())
+    });
+    if threads.is_empty() && !output.trim().is_empty() {
+        return Err(error::Error::ThreadsParse(format!(
+            "cannot parse this string: {}",
+            output
+        )));
+    }
+    Ok(threads)
+}
+
+/// Function splits `apply_all_frames` command output by `#N` frame headers and
+/// returns (frame number, output) pairs in gdb order.
+///
+/// # Arguments
+///
+/// * 'output' - gdb output of `frame apply all` command
+pub fn split_frames(output: &str) -> Vec<(u32, String)> {
+    // #1  0x0000555555555189 in main () at /tmp/test.c:10
+    let re = Regex::new(r"^#(\d+)\s").unwrap();
+    split_by_headers(output, |line| {
+        re.captures(line)
+            .and_then(|caps| caps[1].parse::<u32>().ok())
+    })
+}
+
+/// Function detects stack trace format (gdb, AddressSanitizer or Valgrind) and
+/// parses it with the matching parser. For sanitizer and Valgrind reports only the
+/// first stack trace (crash location) is parsed.
+///
+/// # Arguments
+///
+/// * 'input' - gdb backtrace, sanitizer report or Valgrind report
+pub fn parse_trace(input: &str) -> error::Result<Stacktrace> {
+    // ==1234==ERROR: AddressSanitizer: heap-buffer-overflow on address 0x602000000011
+    let sanitizer = Regex::new(r"(?m)^==\d+==\s*(?:ERROR|WARNING): \w*Sanitizer").unwrap();
+    // ==1234==    at 0x4005D4: main (test.c:5)
+    let valgrind = Regex::new(r"^==\d+==\s+(?:at|by) (0x[0-9a-fA-F]+): (.*)$").unwrap();
+    let frame = Regex::new(r"^#\d+\s").unwrap();
+
+    let trace = if input.lines().any(|line| valgrind.is_match(line)) {
+        // ==1234==    by 0x4E5A2F0: (below main) (in /lib/x86_64-linux-gnu/libc.so.6)
+        let location = Regex::new(r"^(.*) \((?:in )?([^()]*)\)$").unwrap();
+        first_block(input, |line| valgrind.captures(line))
+            .iter()
+            .enumerate()
+            .map(|(i, caps)| match location.captures(&caps[2]) {
+                Some(loc) => format!("#{} {} in {} () {}", i, &caps[1], &loc[1], &loc[2]),
+                None => format!("#{} {} in {} ()", i, &caps[1], &caps[2]),
+            })
+            .collect::<Vec<String>>()
+            .join("\n")
+    } else if sanitizer.is_match(input) {
+        first_block(input, |line| frame.is_match(line).then(|| line.to_string())).join("\n")
+    } else {
+        input.to_string()
+    };
+    Stacktrace::from_gdb(&trace)
+}
+
+/// Function returns the first block of consecutive lines accepted by `item`.
+fn first_block<'a, T>(input: &'a str, item: impl Fn(&'a str) -> Option<T>) -> Vec<T> {
+    input
+        .lines()
+        .map(|line| item(line.trim()))
+        .skip_while(Option::is_none)
+        .map_while(|x| x)
+        .collect()
+}
+
+/// Function splits output into parts that start with header lines.
+/// Lines before the first header are skipped.
+fn split_by_headers<K>(output: &str, header: impl Fn(&str) -> Option<K>) -> Vec<(K, String)> {
+    let mut parts: Vec<(K, Vec<&str>)> = Vec::new();
+    for line in output.lines() {
+        if let Some(key) = header(line) {
+            parts.push((key, Vec::new()));
+        } else if let Some((_, lines)) = parts.last_mut() {
+            lines.push(line);
+        }
+    }
+    parts
+        .into_iter()
+        .map(|(key, lines)| (key, lines.join("\n").trim().to_string()))
+        .collect()
+}
+
+/// Function returns true if `info sources` command output lists at least one source file,
+/// i.e. some module has debugging information.
+///
+/// # Arguments
+///
+/// * 'output' - gdb output of `info sources` command
+pub fn has_debug_sources(output: &str) -> bool {
+    // /tmp/test:
+    //
+    // /tmp/test.c, /usr/include/stdio.h
+    //
+    // /lib/x86_64-linux-gnu/libc.so.6:
+    // (Objfile has no debugging information.)
+    output.lines().map(str::trim).any(|line| {
+        !line.is_empty()
+            && !line.ends_with(':')
+            && !line.starts_with('(')
+            && !line.starts_with("No ")
+    })
+}
+
+/// Type of `gdb` execution: Remote attach to process, local run with args, core (with or without target).
+#[derive(Debug, Clone)]
+pub enum ExecType<'a> {
+    /// Run target program via `gdb` (--args) option.
+    Local(&'a [&'a str]),
+    /// Run target program built with address sanitizer via `gdb` (--args) option.
+    ASan(&'a [&'a str]),
+    /// Attach to process via `gdb` (-p) option.
+    Remote(&'a str),
+    /// Run target via `gdb` with coredump.
+    Core { target: &'a str, core: &'a str },
+    /// Load coredump via `gdb` without target binary (symbols are limited).
+    CoreOnly(&'a str),
+}
+
+/// Command that prints marker separating command results.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MarkerStyle {
+    /// Print marker string with expression evaluator: `p "marker"`.
+    #[default]
+    Print,
+    /// Print marker with echo command: `echo marker\n`.
+    Echo,
+}
+
+/// `PartialResults` struct represents results of gdb run that may be killed.
+#[derive(Clone, Default, Debug)]
+pub struct PartialResults {
+    /// Complete results of executed commands
+    pub results: Vec<String>,
+    /// Commands without complete result
+    pub missing: Vec<String>,
+}
+
+/// `GdbValue` enum represents value printed by gdb `p` command.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum GdbValue {
+    /// Integer (char value is its code)
+    Int(i128),
+    /// Pointer with type if gdb printed it
+    Ptr { ty: Option<String>, address: u64 },
+    /// String (char array or string pointer contents)
+    Str(String),
+    /// Structure fields in declaration order
+    Struct(Vec<(String, GdbValue)>),
+    /// Enumerator name or flag enum combination ("A | B")
+    Enum(String),
+    /// Value that isn't recognized (floats, arrays, etc.)
+    Raw(String),
+}
+
+impl GdbValue {
+    /// Returns GdbValue parsed from `p` command output
+    ///
+    /// # Arguments
+    ///
+    /// * 's' - gdb output of `p` command (`$N = VALUE`) or value
+    pub fn parse(s: &str) -> GdbValue {
+        // $1 = {a = 1, b = 0x4006f4 "text"}
+        let history = Regex::new(r"^\$\d+\s*=\s*").unwrap();
+        let value = s.trim();
+        let value = history.find(value).map_or(value, |m| &value[m.end()..]);
+
+        let int = Regex::new(r"^(-?\d+)(?: '.*')?$").unwrap();
+        let ptr = Regex::new(r"^\(([^()]*\*)\) 0x([0-9a-fA-F]+)(?: <.*>)?$").unwrap();
+        let hex = Regex::new(r#"^0x([0-9a-fA-F]+)(?: (<.*>|".*"))?$"#).unwrap();
+        let name = Regex::new(r"^[A-Za-z_]\w*(?:::\w+)*$").unwrap();
+        if let Some(caps) = int.captures(value) {
+            if let Ok(x) = caps[1].parse::<i128>() {
+                return GdbValue::Int(x);
+            }
+        }
+        if let Some(caps) = ptr.captures(value) {
+            if let Ok(address) = u64::from_str_radix(&caps[2], 16) {
+                return GdbValue::Ptr {
+                    ty: Some(caps[1].to_string()),
+                    address,
+                };
+            }
+        }
+        if let Some(caps) = hex.captures(value) {
+            match (u64::from_str_radix(&caps[1], 16), caps.get(2)) {
+                // p/x output
+                (Ok(x), None) => return GdbValue::Int(x.into()),
+                (Ok(_), Some(s)) if s.as_str().starts_with('"') => {
+                    return GdbValue::Str(Self::unquote(s.as_str()))
+                }
+                (Ok(address), Some(_)) => return GdbValue::Ptr { ty: None, address },
+                _ => {}
+            }
+        }
+        if value.len() > 1 && value.starts_with('"') && value.ends_with('"') {
+            return GdbValue::Str(Self::unquote(value));
+        }
+        if name.is_match(value) {
+            return GdbValue::Enum(value.to_string());
+        }
+        // (FLAG_A | FLAG_B)
+        if let Some(flags) = value.strip_prefix('(').and_then(|x| x.strip_suffix(')')) {
+            if flags
+                .split(" | ")
+                .all(|x| name.is_match(x) || x.starts_with("unknown: "))
+            {
+                return GdbValue::Enum(flags.to_string());
+            }
+        }
+        if let Some(inner) = value.strip_prefix('{').and_then(|x| x.strip_suffix('}')) {
+            let fields = split_list(inner)
+                .iter()
+                .map(|x| {
+                    x.split_once(" = ")
+                        .filter(|(name, _)| !name.contains(['{', '"', '(']))
+                        .map(|(name, value)| (name.trim().to_string(), GdbValue::parse(value)))
+                })
+                .collect::<Option<Vec<(String, GdbValue)>>>();
+            if let Some(fields) = fields.filter(|x| !x.is_empty()) {
+                return GdbValue::Struct(fields);
+            }
+        }
+        GdbValue::Raw(value.to_string())
+    }
+
+    /// Returns string without quotes with simple escapes replaced.
+    fn unquote(s: &str) -> String {
+        let s = s.strip_prefix('"').unwrap_or(s);
+        let s = s.strip_suffix('"').unwrap_or(s);
+        let mut result = String::new();
+        let mut chars = s.chars();
+        while let Some(c) = chars.next() {
+            if c != '\\' {
+                result.push(c);
+                continue;
+            }
+            match chars.next() {
+                Some('n') => result.push('\n'),
+                Some('t') => result.push('\t'),
+                Some(c) => result.push(c),
+                None => result.push('\\'),
+            }
+        }
+        result
+    }
+}
+
+/// `CrashReport` struct represents crash state collected in one gdb run (`report`).
+#[derive(Clone, Debug)]
+pub struct CrashReport {
+    /// Stack trace with modules and offsets filled from mappings
+    pub stacktrace: Stacktrace,
+    /// Register values
+    pub registers: Registers,
+    /// Mapped files
+    pub mappings: MappedFiles,
+    /// Instructions at program counter (x/16i $pc)
+    pub disassembly: Disassembly,
+    /// Signal that stopped the program, None if there is no signal
+    pub stop_reason: Option<StopReason>,
+    /// Crash hash of stack trace
+    pub crash_hash: u64,
+    /// Function that detected memory corruption and aborted the program
+    /// (`CORRUPTION_CHECKS`), None if there is no such frame
+    pub corruption_check: Option<String>,
+}
+
+/// Heuristic crash exploitability class (see `CrashReport::exploitability`).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Exploitability {
+    /// Attacker likely controls program counter or memory write
+    Exploitable,
+    /// Memory corruption detected, but control isn't evident
+    ProbablyExploitable,
+    /// Not enough information to classify
+    Unknown,
+    /// Near-null dereference, division by zero or plain abort
+    NotExploitable,
+}
+
+impl CrashReport {
+    /// Method classifies crash exploitability with simple heuristics in the
+    /// spirit of `!exploitable`:
+    ///
+    /// * SIGABRT after corruption check (`corruption_check`) is probably exploitable,
+    ///   other aborts are not exploitable.
+    /// * SIGILL is probably exploitable (execution of data), SIGFPE isn't exploitable.
+    /// * SIGSEGV/SIGBUS: program counter near null isn't exploitable; program counter
+    ///   outside mapped or executable memory, call/jump via bad memory pointer and
+    ///   memory write to not near-null address are exploitable; memory access near null
+    ///   isn't exploitable; other reads (and stack exhaustion) are unknown.
+    ///
+    /// The faulting instruction is taken from `disassembly` (Intel or AT&T x86 syntax),
+    /// the accessed address is computed from registers for `[base+index*scale+disp]`
+    /// operands only. Heuristics don't detect use-after-free or data-only attacks and
+    /// should be used for triage ordering, not as a verdict.
+    pub fn exploitability(&self) -> Exploitability {
+        // First 64 KiB aren't mapped by default (vm.mmap_min_addr)
+        const NEAR_NULL: u64 = 0x10000;
+        let Some(stop_reason) = &self.stop_reason else {
+            return Exploitability::Unknown;
+        };
+        match stop_reason.signal.as_str() {
+            "SIGABRT" => {
+                return if self.corruption_check.is_some() {
+                    Exploitability::ProbablyExploitable
+                } else {
+                    Exploitability::NotExploitable
+                };
+            }
+            "SIGILL" => return Exploitability::ProbablyExploitable,
+            "SIGFPE" => return Exploitability::NotExploitable,
+            "SIGSEGV" | "SIGBUS" => {}
+            _ => return Exploitability::Unknown,
+        }
+        if let Some(pc) = self.registers.instruction_pointer() {
+            if pc < NEAR_NULL {
+                return Exploitability::NotExploitable;
+            }
+            match self.mappings.find(pc) {
+                None if !self.mappings.files.is_empty() => return Exploitability::Exploitable,
+                Some(file) if !file.perms.is_empty() && !file.perms.contains('x') => {
+                    return Exploitability::Exploitable
+                }
+                _ => {}
+            }
+        }
+        let Some(Instruction {
+            mnemonic, operands, ..
+        }) = self.disassembly.current()
+        else {
+            return Exploitability::Unknown;
+        };
+        let att = operands.iter().any(|x| x.contains('%'));
+        let memory = |x: &String| {
+            if att {
+                x.contains('(')
+            } else {
+                x.contains('[')
+            }
+        };
+        let address = operands
+            .iter()
+            .find(|x| memory(x))
+            .and_then(|x| operand_address(x, &self.registers));
+        if address.is_some_and(|x| x < NEAR_NULL) {
+            return Exploitability::NotExploitable;
+        }
+        // Jump target is read from bad memory
+        if (mnemonic.starts_with("call") || mnemonic.starts_with("jmp"))
+            && operands.iter().any(memory)
+        {
+            return Exploitability::Exploitable;
+        }
+        // Destination is the first operand in Intel syntax and the last one in AT&T
+        let destination = if att {
+            operands.last()
+        } else {
+            operands.first()
+        };
+        if operands.len() > 1 && destination.is_some_and(memory) {
+            return Exploitability::Exploitable;
+        }
+        Exploitability::Unknown
+    }
+}
+
+/// Function splits instruction text into mnemonic and operands.
+fn split_instruction(text: &str) -> (String, Vec<String>) {
+    let mut tokens = text.splitn(2, char::is_whitespace);
+    let mut mnemonic = tokens.next().unwrap_or_default().to_string();
+    let mut operands = tokens.next().unwrap_or_default().trim().to_string();
+    // Skip prefixes: "rep stos BYTE PTR es:[rdi],al", "lock xadd ..."
+    while [
+        "rep", "repz", "repnz", "repe", "repne", "lock", "notrack", "bnd",
+    ]
+    .contains(&mnemonic.as_str())
+    {
+        let rest = operands.clone();
+        let mut tokens = rest.splitn(2, char::is_whitespace);
+        mnemonic = tokens.next().unwrap_or_default().to_string();
+        operands = tokens.next().unwrap_or_default().trim().to_string();
+    }
+    // Split operands by commas outside of brackets: "0x8(%rax,%rbx,4),%rdx"
+    let mut result = split_list(&operands);
+    // Drop symbol comment: "# 0x4010 <buf>"
+    if let Some(last) = result.pop() {
+        let last = last.split('#').next().unwrap_or_default().trim();
+        if !last.is_empty() {
+            result.push(last);
+        }
+    }
+    let result = result.iter().map(|x| x.to_string()).collect();
+    (mnemonic, result)
+}
+
+/// Function computes memory operand address from register values.
+/// Returns None if operand has unknown registers or format.
+fn operand_address(operand: &str, registers: &Registers) -> Option<u64> {
+    // Indirect call/jump in AT&T syntax: *0x8(%rax)
+    let operand = operand.trim_start_matches('*');
+    let value = |term: &str| -> Option<u64> {
+        let term = term.trim().trim_start_matches('%');
+        if let Some(hex) = term.strip_prefix("0x") {
+            return u64::from_str_radix(hex, 16).ok();
+        }
+        if let Ok(num) = term.parse::<u64>() {
+            return Some(num);
+        }
+        // rip-relative address is computed by gdb
+        if term == "rip" || term == "eip" {
+            return None;
+        }
+        registers.get(term)
+    };
+    if let Some((disp, inner)) = operand.split_once('(') {
+        // AT&T: -0x8(%rbp,%rax,4)
+        let inner = inner.strip_suffix(')')?;
+        let disp = disp.rsplit(':').next().unwrap_or_default();
+        let mut address = match disp.strip_prefix('-') {
+            Some(disp) => 0u64.wrapping_sub(value(disp)?),
+            None if disp.is_empty() => 0,
+            None => value(disp)?,
+        };
+        let parts = inner.split(',').collect::<Vec<&str>>();
+        if let Some(base) = parts.first().filter(|x| !x.is_empty()) {
+            address = address.wrapping_add(value(base)?);
+        }
+        if let Some(index) = parts.get(1) {
+            let scale = parts.get(2).map_or(Some(1), |x| value(x))?;
+            address = address.wrapping_add(value(index)?.wrapping_mul(scale));
+        }
+        return Some(address);
+    }
+    // Intel: QWORD PTR [rbp+rax*4-0x8]
+    let start = operand.find('[')?;
+    let inner = operand[start + 1..].strip_suffix(']')?;
+    let mut address = 0u64;
+    let mut sign = '+';
+    let mut term = String::new();
+    for c in inner.chars().chain(std::iter::once('+')) {
+        if c == '+' || c == '-' {
+            let part = match term.split_once('*') {
+                Some((x, scale)) => value(x)?.wrapping_mul(value(scale)?),
+                None => value(&term)?,
+            };
+            address = if sign == '-' {
+                address.wrapping_sub(part)
+            } else {
+                address.wrapping_add(part)
+            };
+            sign = c;
+            term.clear();
+        } else {
+            term.push(c);
+        }
+    }
+    Some(address)
+}
+
+/// Event for gdb catchpoint (catch).
+#[derive(Debug, Clone)]
+pub enum CatchEvent<'c> {
+    /// System call with given name or number, any system call if None.
+    Syscall(Option<&'c str>),
+    /// Signal with given name (SIGSEGV), any signal if None.
+    Signal(Option<&'c str>),
+    /// C++ exception throw.
+    Throw,
+    /// Shared library loading.
+    Load,
+}
+
+impl fmt::Display for CatchEvent<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CatchEvent::Syscall(Some(syscall)) => write!(f, "catch syscall {}", syscall),
+            CatchEvent::Syscall(None) => write!(f, "catch syscall"),
+            CatchEvent::Signal(Some(signal)) => write!(f, "catch signal {}", signal),
+            CatchEvent::Signal(None) => write!(f, "catch signal"),
+            CatchEvent::Throw => write!(f, "catch throw"),
+            CatchEvent::Load => write!(f, "catch load"),
+        }
+    }
+}
+
+/// Temporary file with stdin data, it is removed on drop.
+#[derive(Debug)]
+struct StdinFile {
+    /// Path to file
+    path: PathBuf,
+}
+
+impl StdinFile {
+    /// Write data to new temporary file. File name is random and the file is
+    /// created exclusively (readable only by owner), so existing files and
+    /// symlinks in shared temporary directory are never written.
+    fn new(data: &[u8]) -> io::Result<StdinFile> {
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+        loop {
+            let mut hasher = RandomState::new().build_hasher();
+            hasher.write_u32(process::id());
+            hasher.write_usize(COUNTER.fetch_add(1, Ordering::Relaxed));
+            let path = env::temp_dir().join(format!("gdb-command-stdin-{:016x}", hasher.finish()));
+            let mut options = fs::OpenOptions::new();
+            options.write(true).create_new(true);
+            #[cfg(unix)]
+            options.mode(0o600);
+            let mut file = match options.open(&path) {
+                Ok(file) => file,
+                Err(e) if e.kind() == io::ErrorKind::AlreadyExists => continue,
+                Err(e) => return Err(e),
+            };
+            // File is removed on drop if write fails
+            let stdin_file = StdinFile { path };
+            file.write_all(data)?;
+            return Ok(stdin_file);
+        }
+    }
+}
+
+impl Drop for StdinFile {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/// Struct contains information about arguments for `gdb` to run.
+#[derive(Debug, Clone)]
+pub struct GdbCommand<'a> {
+    /// Gdb execution type.
+    exec_type: ExecType<'a>,
+    /// Execution parameters (-ex).
+    args: Vec<String>,
+    /// Stdin file
+    stdin: Option<&'a PathBuf>,
+    /// Stdin data
+    stdin_bytes: Option<Vec<u8>>,
+    /// Number of gdb restarts when attach to remote process fails
+    attach_retries: u32,
+    /// Delay between attach retries
+    attach_delay: Duration,
+    /// Gdb charset (set charset)
+    charset: Option<String>,
+    /// Unique string printed after each command to split gdb output
+    marker: String,
+    /// Function to stop at (break_and_run)
+    break_function: Option<String>,
+    /// Disassembly flavor for x86 targets
+    disassembly_flavor: Option<String>,
+    /// Emit default setup commands (backtrace limit, disassembly flavor)
+    default_setup: bool,
+    /// Custom setup commands executed before any other command
+    setup_commands: Vec<String>,
+    /// Working directory for executable
+    cwd: Option<PathBuf>,
+    /// List source code after each stepping command
+    list_after_step: bool,
+    /// Print frame arguments mode (set print frame-arguments)
+    frame_arguments: Option<String>,
+    /// Print frame info mode (set print frame-info)
+    frame_info: Option<String>,
+    /// Disable address space randomization (set disable-randomization)
+    disable_randomization: Option<bool>,
+    /// Maximum size of value gdb reads from memory (set max-value-size)
+    max_value_size: Option<String>,
+    /// Command that prints marker
+    marker_style: MarkerStyle,
+    /// Files written by commands (command, path): dump_memory, generate_core
+    output_files: Vec<(String, PathBuf)>,
+    /// Gdb session transcript file (set logging file)
+    log_file: Option<PathBuf>,
+    /// Gdb data directory (--data-directory)
+    data_directory: Option<String>,
+    /// Return error if gdb prints warnings to stderr
+    fail_on_warning: bool,
+}
+
+impl<'a> GdbCommand<'a> {
+    /// Construct `GdbCommand` from given ExecType.
+    /// # Arguments
+    ///
+    /// * `type` - execution type to run gdb.
+    pub fn new(exec_type: &'a ExecType) -> GdbCommand<'a> {
+        GdbCommand {
+            exec_type: exec_type.clone(),
+            args: Vec::new(),
+            stdin: None,
+            stdin_bytes: None,
+            attach_retries: 0,
+            attach_delay: Duration::from_millis(0),
+            charset: None,
+            marker: format!(
+                "gdb-command-{:016x}",
+                RandomState::new().build_hasher().finish()
+            ),
+            break_function: None,
+            disassembly_flavor: Some("intel".to_string()),
+            default_setup: true,
+            setup_commands: Vec::new(),
+            cwd: None,
+            list_after_step: false,
+            frame_arguments: None,
+            frame_info: None,
+            disable_randomization: None,
+            max_value_size: None,
+            marker_style: MarkerStyle::Print,
+            output_files: Vec::new(),
+            log_file: None,
+            data_directory: None,
+            fail_on_warning: false,
+        }
+    }
+
+    /// Add stdin for executable. It is passed to the debugged program with
+    /// `run < file`, while gdb's own stdin is always empty, so prompts that
+    /// `set confirm off` doesn't suppress get EOF and are answered with default.
+    /// # Arguments
+    ///
+    /// * `file` - path to stdin file, it must not contain single quotes and newlines
+    pub fn stdin(&mut self, file: Option<&'a PathBuf>) -> &mut GdbCommand<'a> {
+        self.stdin = file;
+        self.stdin_bytes = None;
+        self
+    }
+
+    /// Set working directory for executable (gdb 'cd' command before run).
+    /// Gdb working directory is changed too, so relative paths in commands
+    /// are resolved against it.
+    /// # Arguments
+    ///
+    /// * `path` - working directory
+    pub fn cwd(&mut self, path: &Path) -> &mut GdbCommand<'a> {
+        self.cwd = Some(path.to_path_buf());
+        self
+    }
+
+    /// Add stdin data for executable. Data is written to temporary file
+    /// which is removed after gdb finishes.
+    /// # Arguments
+    ///
+    /// * `data` - stdin data
+    pub fn stdin_bytes(&mut self, data: Vec<u8>) -> &mut GdbCommand<'a> {
+        self.stdin_bytes = Some(data);
+        self.stdin = None;
+        self
+    }
+
+    /// Add stdin lines for interactive executable. Each line is terminated
+    /// by newline and written to temporary file like `stdin_bytes`.
+    /// # Arguments
+    ///
+    /// * `lines` - stdin lines
+    pub fn stdin_lines(&mut self, lines: &[&str]) -> &mut GdbCommand<'a> {
+        let mut data = Vec::new();
+        for line in lines {
+            data.extend_from_slice(line.as_bytes());
+            data.push(b'\n');
+        }
+        self.stdin_bytes(data)
+    }
+
+    /// Returns (major, minor) version of gdb in use.
+    /// The version is determined once per process by running `gdb --version`.
+    pub fn gdb_version() -> error::Result<(u32, u32)> {
+        if let Some(version) = GDB_VERSION.get() {
+            return version
+                .ok_or_else(|| error::Error::ParseOutput("cannot get gdb version".to_string()));
+        }
+        let version = Self::detect_gdb_version();
+        let _ = GDB_VERSION.set(version.as_ref().ok().copied());
+        version
+    }
+
+    /// Returns gdb version from `gdb --version` output.
+    fn detect_gdb_version() -> error::Result<(u32, u32)> {
+        let output = Command::new("gdb").arg("--version").output()?;
+        let output = String::from_utf8_lossy(&output.stdout);
+        let first_line = output.lines().next().unwrap_or_default();
+        // GNU gdb (Ubuntu 12.1-0ubuntu1~22.04) 12.1
+        let re = Regex::new(r"^GNU gdb .*?(\d+)\.(\d+)\S*$").unwrap();
+        if let Some(caps) = re.captures(first_line) {
+            return Ok((caps[1].parse::<u32>()?, caps[2].parse::<u32>()?));
+        }
+        Err(error::Error::ParseOutput(format!(
+            "cannot get gdb version from this string: {}",
+            output
+        )))
+    }
+
+    /// Returns gdb command with syntax supported by gdb in use.
+    /// Gdb version is checked only for version dependent commands.
+    fn compat_command(cmd: &str) -> &str {
+        // gdb before 11 accepts only ALLOBJ keyword
+        if cmd == ALL_SECTIONS_COMMAND && Self::gdb_version().is_ok_and(|v| v < (11, 0)) {
+            return "maintenance info sections ALLOBJ";
+        }
+        // gdb 12 deprecates 'set logging on'
+        if cmd == LOGGING_COMMAND && Self::gdb_version().is_ok_and(|v| v < (12, 0)) {
+            return "set logging on";
+        }
+        cmd
+    }
+
+    /// Retry gdb execution when attach to remote process fails
+    /// (target is not ready for ptrace yet).
+    /// # Arguments
+    ///
+    /// * `count` - number of retries.
+    ///
+    /// * `delay` - delay between retries.
+    pub fn attach_retries(&mut self, count: u32, delay: Duration) -> &mut GdbCommand<'a> {
+        self.attach_retries = count;
+        self.attach_delay = delay;
+        self
+    }
+
+    /// Set gdb charset (set charset) to get output in given encoding.
+    /// # Arguments
+    ///
+    /// * `charset` - charset name, e.g. "UTF-8".
+    pub fn set_charset(&mut self, charset: &str) -> &mut GdbCommand<'a> {
+        self.charset = Some(charset.to_string());
+        self
+    }
+
+    /// Set disassembly flavor ("intel" by default). Flavor is supported only for
+    /// x86 targets, pass `None` for other architectures (ARM, AArch64, MIPS, ...)
+    /// to avoid gdb errors in output.
+    /// # Arguments
+    ///
+    /// * `flavor` - disassembly flavor ("intel" or "att").
+    pub fn disassembly_flavor(&mut self, flavor: Option<&str>) -> &mut GdbCommand<'a> {
+        self.disassembly_flavor = flavor.map(|f| f.to_string());
+        self
+    }
+
+    /// Set how argument values are printed in backtrace frames. Gdb default is
+    /// "scalars" (non-scalar values are printed as `...`), "none" makes `bt`
+    /// output for deep stacks much shorter and faster to parse.
+    /// # Arguments
+    ///
+    /// * `mode` - frame arguments mode ("none", "scalars", "all" or "presence").
+    pub fn print_frame_arguments(&mut self, mode: &str) -> &mut GdbCommand<'a> {
+        self.frame_arguments = Some(mode.to_string());
+        self
+    }
+
+    /// Set what frame information gdb prints in backtrace and stop location,
+    /// e.g. "source-and-location" or "location" to always print `at file:line`
+    /// location. Source lines printed after frames are skipped by `Stacktrace::from_gdb`.
+    /// # Arguments
+    ///
+    /// * `mode` - frame info mode ("auto", "source-line", "location",
+    ///   "source-and-location", "location-and-address" or "short-location").
+    pub fn print_frame_info(&mut self, mode: &str) -> &mut GdbCommand<'a> {
+        self.frame_info = Some(mode.to_string());
+        self
+    }
+
+    /// Set whether gdb disables address space randomization for the program
+    /// (on by default in gdb). Some crashes reproduce only with ASLR enabled.
+    /// # Arguments
+    ///
+    /// * `on` - disable randomization.
+    pub fn disable_randomization(&mut self, on: bool) -> &mut GdbCommand<'a> {
+        self.disable_randomization = Some(on);
+        self
+    }
+
+    /// Set maximum size of value gdb reads from memory (65536 bytes by default),
+    /// so printing of large buffers and structures doesn't fail.
+    /// # Arguments
+    ///
+    /// * `bytes` - size in bytes (at least 16), `None` for unlimited.
+    pub fn max_value_size(&mut self, bytes: Option<usize>) -> &mut GdbCommand<'a> {
+        self.max_value_size = Some(match bytes {
+            // gdb doesn't accept sizes less than 16 bytes
+            Some(bytes) => bytes.max(16).to_string(),
+            None => "unlimited".to_string(),
+        });
+        self
+    }
+
+    /// Set command that prints marker between command results (`p "marker"` by
+    /// default). Use `MarkerStyle::Echo` if gdb build can't evaluate string literals.
+    /// # Arguments
+    ///
+    /// * `style` - marker command style.
+    pub fn set_marker_style(&mut self, style: MarkerStyle) -> &mut GdbCommand<'a> {
+        let old = self.marker_command();
+        self.marker_style = style;
+        let new = self.marker_command();
+        self.args
+            .iter_mut()
+            .filter(|x| **x == old)
+            .for_each(|x| *x = new.clone());
+        self
+    }
+
+    /// Set gdb data directory (--data-directory) for gdb bundled with application,
+    /// so gdb finds its python modules and doesn't print init warnings.
+    /// # Arguments
+    ///
+    /// * `path` - gdb data directory
+    pub fn data_directory(&mut self, path: &str) -> &mut GdbCommand<'a> {
+        self.data_directory = Some(path.to_string());
+        self
+    }
+
+    /// Return `GdbWarning` error from `raw`, `run`, `run_async` and `run_batch` if gdb
+    /// prints warnings (`warning: ...`, `No symbol table ...`) to stderr, e.g. to catch
+    /// misconfigured symbol paths in CI.
+    pub fn fail_on_gdb_warning(&mut self) -> &mut GdbCommand<'a> {
+        self.fail_on_warning = true;
+        self
+    }
+
+    /// Write gdb session transcript to file (set logging file), e.g. to inspect
+    /// gdb output when parsing fails. Logging is enabled before any other command.
+    /// The file is overwritten.
+    /// # Arguments
+    ///
+    /// * `path` - transcript file path
+    pub fn log_to(&mut self, path: &Path) -> &mut GdbCommand<'a> {
+        self.log_file = Some(path.to_path_buf());
+        self
+    }
+
+    /// Do not emit default setup commands (set backtrace limit 2000,
+    /// set disassembly-flavor).
+    pub fn no_default_setup(&mut self) -> &mut GdbCommand<'a> {
+        self.default_setup = false;
+        self
+    }
+
+    /// Add gdb command to execute before any other command. Its output
+    /// is not returned by `run`.
+    /// # Arguments
+    ///
+    /// * `cmd` - gdb command.
+    pub fn setup_command(&mut self, cmd: &str) -> &mut GdbCommand<'a> {
+        self.setup_commands.push(cmd.to_string());
+        self
+    }
+
+    /// Returns number of commands which results are returned by `run`.
+    pub fn commands_len(&self) -> usize {
+        self.commands().len()
+    }
+
+    /// Returns true if there are no commands which results are returned by `run`.
+    pub fn is_empty(&self) -> bool {
+        self.commands_len() == 0
+    }
+
+    /// Returns gdb command line arguments (without "gdb" itself) for added commands,
+    /// e.g. to log how gdb is run. Stdin data (`stdin_bytes`) is written to temporary
+    /// file only when gdb is run, so its redirection is not included.
+    pub fn command_line(&self) -> error::Result<Vec<String>> {
+        self.gdb_args(&None)
+    }
+
+    /// Add new gdb command to execute.
+    /// # Arguments
+    ///
+    /// * `cmd` - gdb command parameter (-ex).
+    pub fn ex(&mut self, cmd: &str) -> &mut GdbCommand<'a> {
+        self.args.push("-ex".to_string());
+        self.args.push(self.marker_command());
+        self.args.push("-ex".to_string());
+        self.args.push(cmd.to_string());
+        self
+    }
+
+    /// Remove all added gdb commands, keeping execution type and stdin.
+    /// Useful to reuse (clone) one configured `GdbCommand` as a template.
+    pub fn clear_commands(&mut self) -> &mut GdbCommand<'a> {
+        self.args.clear();
+        self.break_function = None;
+        self.output_files.clear();
+        self
+    }
+
+    /// Returns gdb process builder with empty stdin. Gdb reads query answers
+    /// (e.g., "Start it from the beginning? (y or n)") from its own stdin,
+    /// so it must never wait for terminal input.
+    fn gdb_process() -> Command {
+        let mut gdb = Command::new("gdb");
+        gdb.stdin(Stdio::null());
+        gdb
+    }
+
+    /// Run gdb with provided commands and return raw stdout.
+    pub fn raw(&self) -> error::Result<Vec<u8>> {
+        let mut gdb = Self::gdb_process();
+        let stdin = self.stdin_file()?;
+        self.remove_output_files()?;
+
+        // Run gdb and get output
+        let mut output = gdb.args(self.gdb_args(&stdin)?).output()?;
+        if let ExecType::Remote(_) = self.exec_type {
+            let mut retries = self.attach_retries;
+            while retries > 0 && Self::attach_failed(&output) {
+                thread::sleep(self.attach_delay);
+                output = gdb.output()?;
+                retries -= 1;
+            }
+        }
+        self.check_warnings(&output)?;
+        Self::stdout(output)
+    }
+
+    /// Returns `GdbWarning` error if warnings are enabled to be fatal and
+    /// gdb printed them to stderr.
+    fn check_warnings(&self, output: &Output) -> error::Result<()> {
+        if !self.fail_on_warning {
+            return Ok(());
+        }
+        let warnings = String::from_utf8_lossy(&output.stderr)
+            .lines()
+            .map(|x| x.trim())
+            .filter(|x| x.starts_with("warning:") || x.starts_with("No symbol table"))
+            .map(|x| x.to_string())
+            .collect::<Vec<String>>();
+        if warnings.is_empty() {
+            Ok(())
+        } else {
+            Err(error::Error::GdbWarning(warnings))
+        }
+    }
+
+    /// Execute gdb and get complete results even if gdb was killed (e.g., by timeout
+    /// or OOM killer). Result of the command which marker after it isn't printed
+    /// is treated as incomplete. Attach retries are not performed.
+    pub fn run_partial(&self) -> error::Result<PartialResults> {
+        let stdin = self.stdin_file()?;
+        self.remove_output_files()?;
+        let mut output = Self::gdb_process().args(self.gdb_args(&stdin)?).output()?;
+        output.stdout.append(&mut output.stderr);
+
+        let commands = self.commands();
+        let mut results = self.split_output(&output.stdout)?;
+        // Gdb stopped before the marker after the last printed result
+        if results.len() <= commands.len() {
+            results.pop();
+        }
+        results.truncate(commands.len());
+        Ok(PartialResults {
+            missing: commands[results.len()..]
+                .iter()
+                .map(|x| x.to_string())
+                .collect(),
+            results,
+        })
+    }
+
+    /// Execute gdb asynchronously and get result for each command (`async` feature).
+    /// # Return value.
+    ///
+    /// The return value is a vector of strings for each command executed.
+    #[cfg(feature = "async")]
+    pub async fn run_async(&self) -> error::Result<Vec<String>> {
+        let mut gdb = tokio::process::Command::from(Self::gdb_process());
+        let stdin = self.stdin_file()?;
+        self.remove_output_files()?;
+        gdb.args(self.gdb_args(&stdin)?);
+
+        let mut output = gdb.output().await?;
+        if let ExecType::Remote(_) = self.exec_type {
+            let mut retries = self.attach_retries;
+            while retries > 0 && Self::attach_failed(&output) {
+                tokio::time::sleep(self.attach_delay).await;
+                output = gdb.output().await?;
+                retries -= 1;
+            }
+        }
+        self.check_warnings(&output)?;
+        self.parse_output(Self::stdout(output)?)
+    }
+
+    /// Run gdb once for several cores of the same target and get result
+    /// for each command per core (`Core` execution type only).
+    /// # Arguments
+    ///
+    /// * `cores` - paths to core files.
+    ///
+    /// # Return value.
+    ///
+    /// The return value is a vector of command results for each core.
+    /// If gdb stops before finishing all cores, `CommandAborted` error is returned
+    /// with `core-file` command of the first unfinished core.
+    pub fn run_batch(&self, cores: &[&str]) -> error::Result<Vec<Vec<String>>> {
+        let target = if let ExecType::Core { target, .. } = &self.exec_type {
+            *target
+        } else {
+            return Err(error::Error::Unsupported(
+                "batch run is supported only for cores".to_string(),
+            ));
+        };
+        // Check if binary exists
+        if !Path::new(target).exists() {
+            return Err(error::Error::NoFile(target.to_string()));
+        }
+
+        self.check_paths()?;
+        let mut gdb_args = self.setup_args();
+        for core in cores {
+            // Check if core exists
+            if !Path::new(core).exists() {
+                return Err(error::Error::NoFile(core.to_string()));
+            }
+            check_path(Path::new(core), "\n\r")?;
+            check_core(core)?;
+            gdb_args.push("-ex".to_string());
+            gdb_args.push(format!("core-file {}", core));
+            gdb_args.extend(Self::compat_args(&self.args));
+            // Separate output of the last command from the next core loading
+            gdb_args.push("-ex".to_string());
+            gdb_args.push(self.marker_command());
+        }
+        gdb_args.push(target.to_string());
+
+        self.remove_output_files()?;
+        let output = Self::gdb_process().args(&gdb_args).output()?;
+        self.check_warnings(&output)?;
+        let results = self.split_output(&Self::stdout(output)?)?;
+        let commands = self.commands();
+        let mut chunks = results.chunks(commands.len() + 1);
+        let mut batch = Vec::new();
+        for core in cores {
+            let chunk = chunks.next().unwrap_or_default();
+            // Gdb didn't reach the marker after commands for this core (e.g., it crashed)
+            if chunk.len() <= commands.len() {
+                return Err(error::Error::CommandAborted(format!("core-file {}", core)));
+            }
+            Self::check_results(&commands, chunk)?;
+            batch.push(chunk[..commands.len()].to_vec());
+        }
+        Ok(batch)
+    }
+
+    /// Check that paths embedded in setup gdb commands (-ex) don't contain
+    /// characters that break the command.
+    fn check_paths(&self) -> error::Result<()> {
+        if let Some(cwd) = &self.cwd {
+            check_path(cwd, "\n\r")?;
+        }
+        if let Some(path) = &self.log_file {
+            check_path(path, "\n\r")?;
+        }
+        for (_, path) in self.output_files.iter() {
+            // Gdb reads file name up to whitespace
+            check_path(path, " \t\n\r")?;
+        }
+        Ok(())
+    }
+
+    /// Returns gdb arguments that set up gdb before any command.
+    fn setup_args(&self) -> Vec<String> {
+        // Set quiet mode and confirm off
+        let mut gdb_args = vec!["--batch".to_string()];
+        if let Some(path) = &self.data_directory {
+            gdb_args.push("--data-directory".to_string());
+            gdb_args.push(path.clone());
+        }
+        // Enable logging first to capture the whole session
+        if let Some(path) = &self.log_file {
+            gdb_args.push("-ex".to_string());
+            gdb_args.push(format!("set logging file {}", path.display()));
+            gdb_args.push("-ex".to_string());
+            gdb_args.push("set logging overwrite on".to_string());
+            gdb_args.push("-ex".to_string());
+            gdb_args.push(Self::compat_command(LOGGING_COMMAND).to_string());
+        }
+        // --batch disables pagination and confirmation, but init scripts may enable them again
+        gdb_args.push("-ex".to_string());
+        gdb_args.push("set pagination off".to_string());
+        gdb_args.push("-ex".to_string());
+        gdb_args.push("set confirm off".to_string());
+        if self.default_setup {
+            gdb_args.push("-ex".to_string());
+            gdb_args.push("set backtrace limit 2000".to_string());
+            if let Some(flavor) = &self.disassembly_flavor {
+                gdb_args.push("-ex".to_string());
+                gdb_args.push(format!("set disassembly-flavor {}", flavor));
+            }
+        }
+        if let Some(charset) = &self.charset {
+            gdb_args.push("-ex".to_string());
+            gdb_args.push(format!("set charset {}", charset));
+        }
+        if let Some(mode) = &self.frame_arguments {
+            gdb_args.push("-ex".to_string());
+            gdb_args.push(format!("set print frame-arguments {}", mode));
+        }
+        if let Some(mode) = &self.frame_info {
+            gdb_args.push("-ex".to_string());
+            gdb_args.push(format!("set print frame-info {}", mode));
+        }
+        if let Some(on) = self.disable_randomization {
+            gdb_args.push("-ex".to_string());
+            gdb_args.push(format!(
+                "set disable-randomization {}",
+                if on { "on" } else { "off" }
+            ));
+        }
+        if let Some(size) = &self.max_value_size {
+            gdb_args.push("-ex".to_string());
+            gdb_args.push(format!("set max-value-size {}", size));
+        }
+        if let Some(cwd) = &self.cwd {
+            gdb_args.push("-ex".to_string());
+            gdb_args.push(format!("cd {}", cwd.display()));
+        }
+        for cmd in self.setup_commands.iter() {
+            gdb_args.push("-ex".to_string());
+            gdb_args.push(cmd.clone());
         }
+        gdb_args
     }
 
-    /// Add stdin for executable
-    /// # Arguments
-    ///
-    /// * `file` - path to stdin file
-    pub fn stdin(&mut self, file: Option<&'a PathBuf>) -> &'a mut GdbCommand {
-        self.stdin = file;
-        self
+    /// Remove files that commands write (dump_memory, generate_core), so files left
+    /// from previous runs are not taken for results.
+    fn remove_output_files(&self) -> error::Result<()> {
+        for (_, path) in self.output_files.iter() {
+            match fs::remove_file(path) {
+                Err(e) if e.kind() != io::ErrorKind::NotFound => return Err(e.into()),
+                _ => {}
+            }
+        }
+        Ok(())
     }
 
-    /// Add new gdb command to execute.
-    /// # Arguments
-    ///
-    /// * `cmd` - gdb command parameter (-ex).
-    pub fn ex(&mut self, cmd: &'a str) -> &'a mut GdbCommand {
-        self.args.push("-ex");
-        self.args.push("p \"gdb-command\"");
-        self.args.push("-ex");
-        self.args.push(cmd);
-        self
+    /// Returns temporary stdin file if stdin data is set.
+    fn stdin_file(&self) -> error::Result<Option<StdinFile>> {
+        match &self.stdin_bytes {
+            Some(data) => Ok(Some(StdinFile::new(data)?)),
+            None => Ok(None),
+        }
     }
 
-    /// Run gdb with provided commands and return raw stdout.
-    pub fn raw(&self) -> error::Result<Vec<u8>> {
-        let mut gdb = Command::new("gdb");
-        let mut gdb_args = Vec::new();
-
-        // Set quiet mode and confirm off
-        gdb_args.push("--batch");
-        gdb_args.push("-ex");
-        gdb_args.push("set backtrace limit 2000");
-        gdb_args.push("-ex");
-        gdb_args.push("set disassembly-flavor intel");
+    /// Returns gdb command line arguments.
+    /// # Arguments
+    ///
+    /// * `stdin_file` - temporary file with stdin data.
+    fn gdb_args(&self, stdin_file: &Option<StdinFile>) -> error::Result<Vec<String>> {
+        self.check_paths()?;
+        let setup = self.setup_args();
+        let mut gdb_args = setup.iter().map(|a| a.as_str()).collect::<Vec<&str>>();
+        let setup_len = gdb_args.len();
+        let marker_command = self.marker_command();
 
         // Create run command
-        let run_command = if let Some(stdin) = self.stdin {
-            format!("r < {}", stdin.display())
-        } else {
-            "r".to_string()
-        };
+        let stdin = stdin_file
+            .as_ref()
+            .map(|f| f.path.as_path())
+            .or_else(|| self.stdin.map(|p| p.as_path()));
+        if let Some(stdin) = stdin {
+            check_path(stdin, STDIN_FORBIDDEN)?;
+        }
+        // Quote path, so spaces and shell metacharacters are taken literally
+        let redirect = stdin
+            .map(|stdin| format!(" < '{}'", stdin.display()))
+            .unwrap_or_default();
+        let run_command = format!("r{}", redirect);
+        let starti_command = format!("starti{}", redirect);
 
         // Add parameters according to execution
         match &self.exec_type {
@@ -608,15 +3508,19 @@ impl<'a> GdbCommand<'a> {
                     return Err(error::Error::NoFile(args[0].to_string()));
                 }
 
-                gdb_args.append(&mut self.args.clone());
+                gdb_args.extend(self.args.iter().map(|a| a.as_str()));
                 gdb_args.push("-ex");
-                gdb_args.push("p \"gdb-command\"");
+                gdb_args.push(&marker_command);
                 gdb_args.push("--args");
-                if let Some(pos) = gdb_args.iter().position(|&x| x == "r") {
-                    gdb_args[pos] = run_command.as_str();
+                if let Some(pos) = gdb_args.iter().position(|&x| x == "r" || x == "starti") {
+                    gdb_args[pos] = if gdb_args[pos] == "r" {
+                        run_command.as_str()
+                    } else {
+                        starti_command.as_str()
+                    };
                 } else {
-                    gdb_args.insert(5, run_command.as_str());
-                    gdb_args.insert(5, "-ex");
+                    gdb_args.insert(setup_len, run_command.as_str());
+                    gdb_args.insert(setup_len, "-ex");
                 }
                 gdb_args.extend_from_slice(args);
             }
@@ -629,7 +3533,7 @@ impl<'a> GdbCommand<'a> {
                 // We need to stop execution before using gdb user options due to sanitizer abort
                 gdb_args.push("-ex");
                 gdb_args.push("b main");
-                gdb_args.append(&mut self.args.clone());
+                gdb_args.extend(self.args.iter().map(|a| a.as_str()));
                 gdb_args.push("-ex");
                 gdb_args.push("c");
                 gdb_args.push("-ex");
@@ -637,20 +3541,26 @@ impl<'a> GdbCommand<'a> {
                 // if we had a segfault we need to continue program running to get ASan report
                 gdb_args.push("c");
                 gdb_args.push("-ex");
-                gdb_args.push("p \"gdb-command\"");
+                gdb_args.push(&marker_command);
                 gdb_args.push("--args");
-                if let Some(pos) = gdb_args.iter().position(|&x| x == "r") {
-                    gdb_args[pos] = run_command.as_str();
+                if let Some(pos) = gdb_args.iter().position(|&x| x == "r" || x == "starti") {
+                    gdb_args[pos] = if gdb_args[pos] == "r" {
+                        run_command.as_str()
+                    } else {
+                        starti_command.as_str()
+                    };
                 } else {
-                    gdb_args.insert(7, run_command.as_str());
-                    gdb_args.insert(7, "-ex");
+                    gdb_args.insert(setup_len + 2, run_command.as_str());
+                    gdb_args.insert(setup_len + 2, "-ex");
                 }
                 gdb_args.extend_from_slice(args);
             }
             ExecType::Remote(pid) => {
                 gdb_args.push("-p");
                 gdb_args.push(pid);
-                gdb_args.append(&mut self.args.clone());
+                gdb_args.extend(self.args.iter().map(|a| a.as_str()));
+                gdb_args.push("-ex");
+                gdb_args.push(&marker_command);
             }
             ExecType::Core { target, core } => {
                 // Check if binary exists
@@ -662,89 +3572,819 @@ impl<'a> GdbCommand<'a> {
                 if !Path::new(core).exists() {
                     return Err(error::Error::NoFile(core.to_string()));
                 }
-                gdb_args.append(&mut self.args.clone());
-                gdb_args.push(&target);
-                gdb_args.push(&core);
+                check_core(core)?;
+                gdb_args.extend(self.args.iter().map(|a| a.as_str()));
+                gdb_args.push("-ex");
+                gdb_args.push(&marker_command);
+                gdb_args.push(target);
+                gdb_args.push(core);
+            }
+            ExecType::CoreOnly(core) => {
+                // Check if core exists
+                if !Path::new(core).exists() {
+                    return Err(error::Error::NoFile(core.to_string()));
+                }
+                check_core(core)?;
+                gdb_args.extend(self.args.iter().map(|a| a.as_str()));
+                gdb_args.push("-ex");
+                gdb_args.push(&marker_command);
+                gdb_args.push("-c");
+                gdb_args.push(core);
             }
         }
 
-        // Run gdb and get output
-        let mut output = gdb.args(&gdb_args).output()?;
+        Ok(Self::compat_args(&gdb_args))
+    }
+
+    /// Returns gdb arguments with commands (`-ex` values) in syntax supported by
+    /// gdb in use. Other arguments (paths, target arguments) are left unchanged.
+    fn compat_args<S: AsRef<str>>(args: &[S]) -> Vec<String> {
+        let mut command = false;
+        args.iter()
+            .map(|arg| {
+                let arg = arg.as_ref();
+                if command {
+                    command = false;
+                    Self::compat_command(arg).to_string()
+                } else {
+                    command = arg == "-ex";
+                    arg.to_string()
+                }
+            })
+            .collect()
+    }
+
+    /// Returns gdb stdout and stderr if gdb finished successfully.
+    fn stdout(mut output: Output) -> error::Result<Vec<u8>> {
         if output.status.success() {
             output.stdout.append(&mut output.stderr.clone());
             Ok(output.stdout)
         } else {
-            Err(error::Error::ExitCode(output.status.code().unwrap()))
+            Err(Self::exit_error(output.status))
+        }
+    }
+
+    /// Returns error for unsuccessful gdb exit status.
+    fn exit_error(status: ExitStatus) -> error::Error {
+        if let Some(code) = status.code() {
+            return error::Error::ExitCode(code);
         }
+        // No exit code if gdb was terminated by signal
+        #[cfg(unix)]
+        let signal = status.signal().unwrap_or_default();
+        #[cfg(not(unix))]
+        let signal = 0;
+        error::Error::Signal(signal)
+    }
+
+    /// Check if gdb failed to attach to remote process.
+    fn attach_failed(output: &Output) -> bool {
+        [&output.stdout, &output.stderr].iter().any(|out| {
+            let out = String::from_utf8_lossy(out);
+            out.contains("ptrace: Operation not permitted") || out.contains("No such process")
+        })
     }
 
     /// Add command to run program
-    pub fn r(&mut self) -> &'a mut GdbCommand {
-        self.args.push("-ex");
-        self.args.push("r");
+    pub fn r(&mut self) -> &mut GdbCommand<'a> {
+        self.args.push("-ex".to_string());
+        self.args.push("r".to_string());
+        self
+    }
+
+    /// Add command to run program and stop at the very first instruction
+    pub fn starti(&mut self) -> &mut GdbCommand<'a> {
+        self.args.push("-ex".to_string());
+        self.args.push("starti".to_string());
         self
     }
 
     /// Add command to continue execution
-    pub fn c(&mut self) -> &'a mut GdbCommand {
-        self.args.push("-ex");
-        self.args.push("c");
+    pub fn c(&mut self) -> &mut GdbCommand<'a> {
+        self.args.push("-ex".to_string());
+        self.args.push("c".to_string());
+        self
+    }
+
+    /// Add command to set catchpoint, so program stops at given event.
+    /// Use `catchpoint_hit` to get hit line from `raw` output after `r` or `c`.
+    /// # Arguments
+    ///
+    /// * `event` - event to catch.
+    pub fn catch(&mut self, event: CatchEvent) -> &mut GdbCommand<'a> {
+        self.args.push("-ex".to_string());
+        self.args.push(event.to_string());
+        self
+    }
+
+    /// Add breakpoint with commands that are executed each time it is hit
+    /// (like gdb `commands ... end` block). Add `continue` as the last command
+    /// to resume execution. Requires gdb 8.3+ with python.
+    /// # Arguments
+    ///
+    /// * `location` - breakpoint location.
+    ///
+    /// * `cmds` - gdb commands.
+    pub fn on_breakpoint(&mut self, location: &str, cmds: &[&str]) -> &mut GdbCommand<'a> {
+        let escape = |s: &str| s.replace('\\', "\\\\").replace('"', "\\\"");
+        let cmds = cmds.iter().map(|c| escape(c)).collect::<Vec<String>>();
+        self.args.push("-ex".to_string());
+        self.args.push(format!(
+            "python gdb.Breakpoint(\"{}\").commands = \"{}\"",
+            escape(location),
+            cmds.join("\\n")
+        ));
+        self
+    }
+
+    /// Add command to delete all breakpoints
+    pub fn delete_breakpoints(&mut self) -> &mut GdbCommand<'a> {
+        self.args.push("-ex".to_string());
+        self.args.push("delete".to_string());
+        self
+    }
+
+    /// Add command to disable breakpoint
+    /// # Arguments
+    ///
+    /// * `n` - breakpoint number.
+    pub fn disable_breakpoint(&mut self, n: u32) -> &mut GdbCommand<'a> {
+        self.args.push("-ex".to_string());
+        self.args.push(format!("disable {}", n));
+        self
+    }
+
+    /// Add commands to set temporary breakpoint on function and run program,
+    /// so the following commands get program state at function entry.
+    /// If the function is not reached, `run` returns `CommandFailed` error.
+    /// # Arguments
+    ///
+    /// * `function` - function to stop at.
+    pub fn break_and_run(&mut self, function: &str) -> &mut GdbCommand<'a> {
+        self.args.push("-ex".to_string());
+        self.args.push(format!("tbreak {}", function));
+        self.break_function = Some(function.to_string());
+        self.r()
+    }
+
+    /// Add command to start recording execution for reverse debugging (-ex 'record full').
+    /// Program must be running (e.g., after `break_and_run` or `starti`).
+    /// Recording slows execution down by orders of magnitude and is supported
+    /// only for native targets on some architectures. Command result contains
+    /// gdb `Process record ...` message if recording failed.
+    pub fn record(&mut self) -> &mut GdbCommand<'a> {
+        self.ex("record full")
+    }
+
+    /// Add command to execute program backward until breakpoint or the beginning
+    /// of recorded history (-ex reverse-continue). Requires `record`.
+    pub fn reverse_continue(&mut self) -> &mut GdbCommand<'a> {
+        self.ex("reverse-continue")
+    }
+
+    /// Add command to set scheduler locking mode, so other threads don't run
+    /// while stepping the current one. Add it after program is started,
+    /// gdb doesn't support scheduler locking before that.
+    /// # Arguments
+    ///
+    /// * `mode` - scheduler locking mode ("off", "on", "step" or "replay").
+    pub fn scheduler_locking(&mut self, mode: &str) -> error::Result<&mut GdbCommand<'a>> {
+        if !["off", "on", "step", "replay"].contains(&mode) {
+            return Err(error::Error::Unsupported(format!(
+                "scheduler-locking mode {}",
+                mode
+            )));
+        }
+        self.args.push("-ex".to_string());
+        self.args.push(format!("set scheduler-locking {}", mode));
+        Ok(self)
+    }
+
+    /// Add command to set how gdb handles signal (-ex 'handle SIG ACTIONS'),
+    /// e.g. to continue past benign SIGPIPE to a later crash.
+    /// # Arguments
+    ///
+    /// * `sig` - signal name (SIGPIPE), number or "all".
+    ///
+    /// * `actions` - "stop", "nostop", "print", "noprint", "pass", "nopass",
+    ///   "ignore" or "noignore".
+    pub fn handle_signal(
+        &mut self,
+        sig: &str,
+        actions: &[&str],
+    ) -> error::Result<&mut GdbCommand<'a>> {
+        const ACTIONS: &[&str] = &[
+            "stop", "nostop", "print", "noprint", "pass", "nopass", "ignore", "noignore",
+        ];
+        if sig.is_empty() || sig.contains(char::is_whitespace) {
+            return Err(error::Error::Unsupported(format!("signal '{}'", sig)));
+        }
+        if let Some(action) = actions.iter().find(|x| !ACTIONS.contains(x)) {
+            return Err(error::Error::Unsupported(format!(
+                "signal action {}",
+                action
+            )));
+        }
+        self.args.push("-ex".to_string());
+        self.args
+            .push(format!("handle {} {}", sig, actions.join(" ")));
+        Ok(self)
+    }
+
+    /// List source code around the current instruction (-ex 'list *$pc') after
+    /// each following stepping command. Listing is a separate command result.
+    pub fn list_after_step(&mut self) -> &mut GdbCommand<'a> {
+        self.list_after_step = true;
+        self
+    }
+
+    /// Add command to step over `n` source lines (-ex 'n N')
+    pub fn next(&mut self, n: u32) -> &mut GdbCommand<'a> {
+        self.step_command(&format!("n {}", n))
+    }
+
+    /// Add command to step into `n` source lines (-ex 's N')
+    pub fn step(&mut self, n: u32) -> &mut GdbCommand<'a> {
+        self.step_command(&format!("s {}", n))
+    }
+
+    /// Add command to step one instruction (-ex si)
+    pub fn stepi(&mut self) -> &mut GdbCommand<'a> {
+        self.step_command("si")
+    }
+
+    /// Add command to step one instruction over calls (-ex ni)
+    pub fn nexti(&mut self) -> &mut GdbCommand<'a> {
+        self.step_command("ni")
+    }
+
+    /// Add stepping command and source listing if needed.
+    fn step_command(&mut self, cmd: &str) -> &mut GdbCommand<'a> {
+        self.ex(cmd);
+        if self.list_after_step {
+            self.ex("list *$pc");
+        }
         self
     }
 
     /// Add command to get backtrace (-ex bt)
-    pub fn bt(&mut self) -> &'a mut GdbCommand {
+    pub fn bt(&mut self) -> &mut GdbCommand<'a> {
         self.ex("bt")
     }
 
+    /// Add command to get backtrace with local variables (-ex 'bt full')
+    pub fn bt_full(&mut self) -> &mut GdbCommand<'a> {
+        self.ex("bt full")
+    }
+
     /// Add command to get disassembly (-ex 'x/16i $pc')
-    pub fn disassembly(&mut self) -> &'a mut GdbCommand {
+    pub fn disassembly(&mut self) -> &mut GdbCommand<'a> {
         self.ex("x/16i $pc")
     }
 
     /// Add command to get registers (-ex 'i r')
-    pub fn regs(&mut self) -> &'a mut GdbCommand {
+    pub fn regs(&mut self) -> &mut GdbCommand<'a> {
         self.ex("i r")
     }
 
     /// Add command to get mappings (-ex 'info proc mappings')
-    pub fn mappings(&mut self) -> &'a mut GdbCommand {
+    pub fn mappings(&mut self) -> &mut GdbCommand<'a> {
         self.ex("info proc mappings")
     }
 
+    /// Add commands to get mappings of remote target or core (-ex 'info proc mappings'
+    /// -ex 'maintenance info sections -all-objects'). Emulators (qemu-user gdbstub)
+    /// don't support mappings and gdb can't get them from some cores, use
+    /// `MappedFiles::from_remote` to fall back to sections.
+    pub fn remote_mappings(&mut self) -> &mut GdbCommand<'a> {
+        // Adapted for gdb before 11 when gdb arguments are built
+        self.mappings().ex(ALL_SECTIONS_COMMAND)
+    }
+
+    /// Add command to get sections (-ex 'maintenance info sections')
+    pub fn sections(&mut self) -> &mut GdbCommand<'a> {
+        self.ex("maintenance info sections")
+    }
+
+    /// Add command to get address range of source line (-ex 'info line LOCATION')
+    /// # Arguments
+    ///
+    /// * `location` - source location (file:line, function, *address)
+    pub fn info_line(&mut self, location: &str) -> &mut GdbCommand<'a> {
+        self.ex(&format!("info line {}", location))
+    }
+
+    /// Add command to get faulting memory address (-ex 'p $_siginfo._sifields._sigfault.si_addr').
+    /// Use `fault_address` to parse the result.
+    pub fn siginfo(&mut self) -> &mut GdbCommand<'a> {
+        self.ex("p $_siginfo._sifields._sigfault.si_addr")
+    }
+
+    /// Add command to get target architecture (-ex 'show architecture').
+    /// Use `architecture` to parse the result.
+    pub fn show_architecture(&mut self) -> &mut GdbCommand<'a> {
+        self.ex("show architecture")
+    }
+
+    /// Add command to print thread-local expression in each thread
+    /// (-ex 'thread apply all p EXPR'). Use `tls_values` to parse the result.
+    /// # Arguments
+    ///
+    /// * `expr` - thread-local expression, e.g. "errno".
+    pub fn print_tls(&mut self, expr: &str) -> &mut GdbCommand<'a> {
+        self.apply_all_threads(&format!("p {}", expr))
+    }
+
+    /// Add command to execute gdb command in each thread (-ex 'thread apply all CMD').
+    /// Use `split_threads` to get output for each thread.
+    /// # Arguments
+    ///
+    /// * `cmd` - gdb command, e.g. "bt" or "p $pc".
+    pub fn apply_all_threads(&mut self, cmd: &str) -> &mut GdbCommand<'a> {
+        self.ex(&format!("thread apply all {}", cmd))
+    }
+
+    /// Add command to execute gdb command in each frame of current thread
+    /// (-ex 'frame apply all CMD'), gdb 9+. Use `split_frames` to get output for each frame.
+    /// # Arguments
+    ///
+    /// * `cmd` - gdb command, e.g. "info locals" or "p $sp".
+    pub fn apply_all_frames(&mut self, cmd: &str) -> &mut GdbCommand<'a> {
+        self.ex(&format!("frame apply all {}", cmd))
+    }
+
+    /// Add command to get unwinding details of selected frame (-ex 'info frame')
+    pub fn info_frame(&mut self) -> &mut GdbCommand<'a> {
+        self.ex("info frame")
+    }
+
+    /// Add command to get loaded shared libraries (-ex 'info sharedlibrary')
+    pub fn shared_libraries(&mut self) -> &mut GdbCommand<'a> {
+        self.ex("info sharedlibrary")
+    }
+
+    /// Add command to get symbol address (-ex 'info address SYMBOL'), e.g. for
+    /// `*0xADDR` breakpoints. Use `symbol_address` to parse the result.
+    /// # Arguments
+    ///
+    /// * `symbol` - function or global variable name.
+    pub fn address_of(&mut self, symbol: &str) -> &mut GdbCommand<'a> {
+        self.ex(&format!("info address {}", symbol))
+    }
+
+    /// Add command to get symbol that contains address (-ex 'info symbol ADDR')
+    /// # Arguments
+    ///
+    /// * `addr` - address expression, e.g. "0x7ffff7e1a2b4" or "$pc".
+    pub fn info_symbol(&mut self, addr: &str) -> &mut GdbCommand<'a> {
+        self.ex(&format!("info symbol {}", addr))
+    }
+
+    /// Add command to get build-id of each loaded module (objfile).
+    /// Use `build_ids` function to parse the result.
+    pub fn build_ids(&mut self) -> &mut GdbCommand<'a> {
+        self.python(
+            "for o in gdb.objfiles():\n    print('%s\\t%s' % (o.filename, o.build_id or ''))",
+        )
+    }
+
+    /// Add command to get instruction bytes at program counter (-ex 'x/Nxb $pc').
+    /// Use `memory_bytes` to parse the result.
+    /// # Arguments
+    ///
+    /// * `count` - number of bytes.
+    pub fn faulting_bytes(&mut self, count: u32) -> &mut GdbCommand<'a> {
+        self.ex(&format!("x/{}xb $pc", count))
+    }
+
+    /// Add command to dump memory region to file
+    /// (-ex 'dump binary memory FILE START END').
+    /// Existing file is removed before gdb is run. If the file doesn't exist
+    /// after gdb finishes, `run` returns `CommandFailed` error.
+    /// # Arguments
+    ///
+    /// * `path` - output file path, it must not contain whitespace.
+    ///
+    /// * `start` - start address expression, e.g. "0x555555559000" or "$sp".
+    ///
+    /// * `end` - end address expression (exclusive).
+    pub fn dump_memory(&mut self, path: &Path, start: &str, end: &str) -> &mut GdbCommand<'a> {
+        let command = format!("dump binary memory {} {} {}", path.display(), start, end);
+        self.output_files
+            .push((command.clone(), path.to_path_buf()));
+        self.ex(&command)
+    }
+
+    /// Add command to save core file of the debugged process (-ex 'generate-core-file FILE'),
+    /// e.g. a snapshot of attached live process (`ExecType::Remote`).
+    /// Existing file is removed before gdb is run. If the file doesn't exist
+    /// after gdb finishes, `run` returns `CommandFailed` error.
+    /// Use `saved_core` to parse the result.
+    /// # Arguments
+    ///
+    /// * `path` - core file path, it must not contain whitespace.
+    pub fn generate_core(&mut self, path: &Path) -> &mut GdbCommand<'a> {
+        let command = format!("generate-core-file {}", path.display());
+        self.output_files
+            .push((command.clone(), path.to_path_buf()));
+        self.ex(&command)
+    }
+
+    /// Add command to get cmd line, cwd, exe, mappings and status at once (-ex 'info proc all')
+    pub fn info_proc_all(&mut self) -> &mut GdbCommand<'a> {
+        self.ex("info proc all")
+    }
+
     /// Add command to get cmd line.
-    pub fn cmdline(&mut self) -> &'a mut GdbCommand {
+    pub fn cmdline(&mut self) -> &mut GdbCommand<'a> {
         self.ex("info proc cmdline")
     }
 
-    /// Add command to get environment variables
-    pub fn env(&mut self) -> &'a mut GdbCommand {
+    /// Add command to get environment variables.
+    /// Use `parse_environment` to parse the result.
+    pub fn env(&mut self) -> &mut GdbCommand<'a> {
         self.ex("show environment")
     }
 
     /// Add command to get process status
-    pub fn status(&mut self) -> &'a mut GdbCommand {
+    pub fn status(&mut self) -> &mut GdbCommand<'a> {
         self.ex("info proc status")
     }
 
     /// Add command to get info
-    pub fn sources(&mut self) -> &'a mut GdbCommand {
+    pub fn sources(&mut self) -> &mut GdbCommand<'a> {
         self.ex("info sources")
     }
 
+    /// Add python script to execute (-ex 'python ...').
+    /// Multi-line script is passed to gdb as a single command.
+    ///
+    /// # Arguments
+    ///
+    /// * `script` - python script, it may use gdb python API.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use gdb_command::*;
+    ///
+    /// // Print backtrace lines as JSON array.
+    /// let result = GdbCommand::new(&ExecType::Local(&["tests/bins/test_abort", "A"]))
+    ///     .python(
+    ///         "import json\n\
+    ///          bt = gdb.execute('bt', to_string=True)\n\
+    ///          print(json.dumps(bt.splitlines()))",
+    ///     )
+    ///     .run()
+    ///     .unwrap();
+    /// assert!(result[0].starts_with('['));
+    /// ```
+    pub fn python(&mut self, script: &str) -> &mut GdbCommand<'a> {
+        self.ex(&Self::python_command(script))
+    }
+
+    /// Add commands that are executed only if program received signal.
+    /// Otherwise (program exited or stopped at breakpoint or after step),
+    /// their results are empty.
+    /// # Arguments
+    ///
+    /// * `f` - function that adds commands.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use gdb_command::*;
+    ///
+    /// let result = GdbCommand::new(&ExecType::Local(&["tests/bins/test_abort", "A"]))
+    ///     .r()
+    ///     .on_crash(|cmd| {
+    ///         cmd.bt().regs();
+    ///     })
+    ///     .run()
+    ///     .unwrap();
+    /// ```
+    pub fn on_crash(&mut self, f: impl FnOnce(&mut GdbCommand<'a>)) -> &mut GdbCommand<'a> {
+        let start = self.args.len();
+        f(self);
+        let marker_command = self.marker_command();
+        for i in (start + 1..self.args.len()).step_by(2) {
+            if self.args[i] == marker_command {
+                continue;
+            }
+            let cmd = self.args[i].replace('\\', "\\\\").replace('\'', "\\'");
+            // Breakpoint and single-step stops are reported as SIGTRAP (5)
+            self.args[i] = Self::python_command(&format!(
+                "try:\n    crashed = int(gdb.parse_and_eval('$_siginfo.si_signo')) not in (0, 5)\n\
+                 except gdb.error:\n    crashed = False\n\
+                 if crashed:\n    gdb.execute('{}')",
+                cmd
+            ));
+        }
+        self
+    }
+
+    /// Returns gdb command that executes python script.
+    fn python_command(script: &str) -> String {
+        if script.contains('\n') {
+            let escaped = script
+                .replace('\\', "\\\\")
+                .replace('"', "\\\"")
+                .replace('\n', "\\n");
+            format!("python exec(\"{}\")", escaped)
+        } else {
+            format!("python {}", script)
+        }
+    }
+
     /// Execute gdb and get result for each command.
     /// # Return value.
     ///
     /// The return value is a vector of strings for each command executed.
     pub fn run(&self) -> error::Result<Vec<String>> {
+        debug_assert!(!self.is_empty(), "no commands that produce results");
+        self.parse_output(self.raw()?)
+    }
+
+    /// Execute gdb with configured commands followed by `bt` and `remote_mappings`
+    /// and get stack trace with modules and offsets filled from mappings
+    /// (or sections if mappings are unavailable).
+    pub fn bt_with_offsets(&self) -> error::Result<Stacktrace> {
+        let n = self.commands().len();
+        let results = self.clone().bt().remote_mappings().run()?;
+        let mut stacktrace = Stacktrace::from_gdb(&results[n])?;
+        stacktrace.update_modules(&MappedFiles::from_remote(&results[n + 1], &results[n + 2])?);
+        Ok(stacktrace)
+    }
+
+    /// Execute gdb with configured commands followed by `info sources` and
+    /// `info sharedlibrary` and check whether any debugging information is loaded.
+    pub fn has_debug_info(&self) -> error::Result<bool> {
+        let n = self.commands().len();
+        let results = match self.clone().sources().shared_libraries().run() {
+            Ok(results) => results,
+            // No module has symbols
+            Err(error::Error::CommandFailed { command, message })
+                if command == "info sources"
+                    && message.starts_with("No symbol table is loaded") =>
+            {
+                return Ok(false)
+            }
+            Err(e) => return Err(e),
+        };
+        if has_debug_sources(&results[n]) {
+            return Ok(true);
+        }
+        Ok(SharedLibraries::from_gdb(&results[n + 1])
+            .is_ok_and(|libs| libs.libraries.iter().any(|x| x.debug_info)))
+    }
+
+    /// Execute gdb with configured commands followed by `bt`, `i r`, `x/16i $pc`
+    /// and `remote_mappings` and collect crash report. Local program must be run by
+    /// configured commands (e.g., `r`).
+    pub fn report(&self) -> error::Result<CrashReport> {
+        let n = self.commands().len();
+        let mut cmd = self.clone();
+        cmd.bt().regs().disassembly().remote_mappings();
+        let raw = cmd.raw()?;
+        let stop_reason = StopReason::from_gdb(&String::from_utf8_lossy(&raw));
+        let results = cmd.parse_output(raw)?;
+        let mappings = MappedFiles::from_remote(&results[n + 3], &results[n + 4])?;
+        let mut stacktrace = Stacktrace::from_gdb(&results[n])?;
+        // Function names are replaced with modules by update_modules
+        let corruption_check = stacktrace
+            .strace
+            .iter()
+            .find(|x| function_matches(x, CORRUPTION_CHECKS))
+            .and_then(|x| x.function_name());
+        stacktrace.update_modules(&mappings);
+        Ok(CrashReport {
+            corruption_check,
+            crash_hash: stacktrace.crash_hash(),
+            stacktrace,
+            registers: Registers::from_gdb(&results[n + 1])?,
+            mappings,
+            disassembly: Disassembly::from_gdb(&results[n + 2])?,
+            stop_reason,
+        })
+    }
+
+    /// Execute gdb with configured commands and get file name of the crashing
+    /// module (see `Stacktrace::crashing_module`).
+    pub fn crashing_module(&self) -> error::Result<Option<String>> {
+        Ok(self.bt_with_offsets()?.crashing_module())
+    }
+
+    /// Execute gdb and pass its output to callback line by line without
+    /// buffering all results. Attach retries are not performed.
+    /// # Arguments
+    ///
+    /// * `on_line` - callback that gets command index and output line.
+    ///   Gdb stderr lines are passed after stdout with the last command index.
+    ///
+    /// If gdb stops before the last command is finished, `CommandAborted` error is
+    /// returned with the unfinished command.
+    pub fn run_streaming(&self, mut on_line: impl FnMut(usize, &str)) -> error::Result<()> {
+        let stdin = self.stdin_file()?;
+        self.remove_output_files()?;
+        let mut child = Self::gdb_process()
+            .args(self.gdb_args(&stdin)?)
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()?;
+
+        // Read stderr concurrently to avoid blocking gdb on full pipe
+        let mut stderr = child.stderr.take().unwrap();
+        let stderr = thread::spawn(move || {
+            let mut buf = Vec::new();
+            stderr.read_to_end(&mut buf).map(|_| buf)
+        });
+
+        let re = self.marker_regex();
+        let commands = self.commands();
+        // Number of printed markers, the last one is printed after all commands
+        let mut markers = 0;
+        let mut stdout = BufReader::new(child.stdout.take().unwrap());
+        let mut line = Vec::new();
+        while stdout.read_until(b'\n', &mut line)? != 0 {
+            let text = String::from_utf8_lossy(&line);
+            let text = text.trim_end_matches(['\n', '\r']);
+            if re.is_match(text) {
+                markers += 1;
+            } else if markers > 0 && markers <= commands.len() {
+                on_line(markers - 1, text);
+            }
+            line.clear();
+        }
+
+        let stderr = stderr
+            .join()
+            .map_err(|_| error::Error::ParseOutput("cannot read gdb stderr".to_string()))??;
+        if markers > 0 {
+            let i = (markers - 1).min(commands.len().saturating_sub(1));
+            String::from_utf8_lossy(&stderr)
+                .lines()
+                .for_each(|line| on_line(i, line));
+        }
+
+        let status = child.wait()?;
+        if !status.success() {
+            return Err(Self::exit_error(status));
+        }
+        if markers <= commands.len() {
+            let command = commands[markers.saturating_sub(1)];
+            return Err(error::Error::CommandAborted(command.to_string()));
+        }
+        Ok(())
+    }
+
+    /// Execute gdb and get results for each command joined by separator.
+    /// # Arguments
+    ///
+    /// * `sep` - separator between results.
+    pub fn run_joined(&self, sep: &str) -> error::Result<String> {
+        Ok(self.run()?.join(sep))
+    }
+
+    /// Execute gdb and get raw output with byte range of each command result in it.
+    /// Ranges allow to re-parse single command result without re-running gdb.
+    /// `run` splits output by the same ranges, but also normalizes CRLF and removes
+    /// pagination prompts and thread notices, which raw ranges still contain.
+    pub fn raw_split(&self) -> error::Result<(Vec<u8>, Vec<Range<usize>>)> {
         let stdout = self.raw()?;
-        let output = String::from_utf8(stdout).unwrap();
-        let re = Regex::new(r#"(?m)^\$\d+\s*=\s*"gdb-command"$"#).unwrap();
-        let mut result = re
-            .split(&output)
-            .map(|s| s.trim().to_string())
-            .collect::<Vec<String>>();
-        result.remove(0);
+        let mut ranges = self.split_ranges(&stdout);
+        // Skip output after the last command
+        ranges.truncate(self.commands().len());
+        Ok((stdout, ranges))
+    }
+
+    /// Split raw gdb output (see `raw`) into results for each command and check them.
+    /// `CommandAborted` error is returned if gdb didn't finish all commands.
+    ///
+    /// # Arguments
+    ///
+    /// * `stdout` - raw gdb output.
+    pub fn parse_output(&self, stdout: Vec<u8>) -> error::Result<Vec<String>> {
+        if let Some(function) = &self.break_function {
+            let re = Regex::new(r"(?m)^Temporary breakpoint \d+, ").unwrap();
+            if !re.is_match(&String::from_utf8_lossy(&stdout)) {
+                return Err(error::Error::CommandFailed {
+                    command: format!("tbreak {}", function),
+                    message: "program exited or crashed before the function is reached".to_string(),
+                });
+            }
+        }
+        let commands = self.commands();
+        let mut result = self.split_output(&stdout)?;
+        Self::check_results(&commands, &result)?;
+        // Skip output after the last command
+        result.truncate(commands.len());
+        // Gdb prints nothing on successful dump
+        if let Some((command, path)) = self.output_files.iter().find(|(_, x)| !x.exists()) {
+            return Err(error::Error::CommandFailed {
+                command: command.clone(),
+                message: format!("file {} was not written", path.display()),
+            });
+        }
         Ok(result)
     }
+
+    /// Split raw gdb output into results for each command.
+    fn split_output(&self, stdout: &[u8]) -> error::Result<Vec<String>> {
+        self.split_ranges(stdout)
+            .into_iter()
+            .map(|range| {
+                String::from_utf8(stdout[range].to_vec())
+                    // Windows gdb builds print CRLF line endings
+                    .map(|result| {
+                        remove_pagination(&remove_notices(&result.replace("\r\n", "\n")))
+                            .trim()
+                            .to_string()
+                    })
+                    .map_err(|e| error::Error::ParseOutput(e.to_string()))
+            })
+            .collect()
+    }
+
+    /// Returns byte range of each command result in raw gdb output.
+    fn split_ranges(&self, stdout: &[u8]) -> Vec<Range<usize>> {
+        let re = regex::bytes::Regex::new(self.marker_regex().as_str()).unwrap();
+        let mut ranges = Vec::new();
+        let mut start = 0;
+        for m in re.find_iter(stdout) {
+            ranges.push(start..m.start());
+            start = m.end();
+        }
+        ranges.push(start..stdout.len());
+        // Skip output before the first command
+        ranges.remove(0);
+
+        // Trim whitespaces
+        for range in ranges.iter_mut() {
+            while range.start < range.end && stdout[range.start].is_ascii_whitespace() {
+                range.start += 1;
+            }
+            while range.end > range.start && stdout[range.end - 1].is_ascii_whitespace() {
+                range.end -= 1;
+            }
+        }
+        ranges
+    }
+
+    /// Returns regex that matches marker string printed by gdb. Some gdb builds
+    /// print whitespaces or thread notification (`[Thread ...]`) before `$N = `.
+    fn marker_regex(&self) -> Regex {
+        let value = match self.marker_style {
+            MarkerStyle::Print => format!(r#"\$\d+\s*=\s*"{}""#, regex::escape(&self.marker)),
+            MarkerStyle::Echo => regex::escape(&self.marker),
+        };
+        Regex::new(&format!(
+            r#"(?m)^[ \t]*(?:\[[^\]\n]*\][ \t]*)?{}[ \t\r]*$"#,
+            value
+        ))
+        .unwrap()
+    }
+
+    /// Returns gdb command that prints marker string.
+    fn marker_command(&self) -> String {
+        match self.marker_style {
+            MarkerStyle::Print => format!("p \"{}\"", self.marker),
+            MarkerStyle::Echo => format!("echo {}\\n", self.marker),
+        }
+    }
+
+    /// Returns gdb commands which results are returned by `run`.
+    fn commands(&self) -> Vec<&str> {
+        let marker_command = self.marker_command();
+        self.args
+            .windows(3)
+            .filter(|w| w[0] == marker_command && w[1] == "-ex")
+            .map(|w| w[2].as_str())
+            .collect()
+    }
+
+    /// Check that gdb did not print known error message instead of command result.
+    ///
+    /// # Arguments
+    ///
+    /// * `commands` - executed gdb commands.
+    ///
+    /// * `results` - output for each command followed by output after the last marker.
+    fn check_results(commands: &[&str], results: &[String]) -> error::Result<()> {
+        // Gdb didn't reach the marker after some command (e.g., it was killed)
+        if results.len() <= commands.len() {
+            if let Some(command) = commands.get(results.len().saturating_sub(1)) {
+                return Err(error::Error::CommandAborted(command.to_string()));
+            }
+        }
+        for (command, output) in commands.iter().zip(results.iter()) {
+            let line = output.lines().next().unwrap_or_default();
+            if GDB_ERRORS.iter().any(|e| line.starts_with(e)) {
+                return Err(error::Error::CommandFailed {
+                    command: command.to_string(),
+                    message: line.to_string(),
+                });
+            }
+        }
+        Ok(())
+    }
 }
@@ -1,8 +1,13 @@
 use gdb_command::*;
 use std::collections::HashSet;
 
+/// Returns true if gdb command line contains command `cmd` (-ex).
+fn has_ex(argv: &[String], cmd: &str) -> bool {
+    argv.windows(2).any(|w| w[0] == "-ex" && w[1] == cmd)
+}
+
 /// Returns an absolute path for relative path.
-fn abs_path<'a>(rpath: &'a str) -> String {
+fn abs_path(rpath: &str) -> String {
     use std::path::PathBuf;
 
     // Define paths.
@@ -14,34 +19,44 @@ fn abs_path<'a>(rpath: &'a str) -> String {
     path.as_os_str().to_str().unwrap().to_string()
 }
 
+/// Runs `f` with gdb command for local run of test binary `bin` (relative path)
+/// with arguments `args`.
+fn with_local<T>(bin: &str, args: &[&str], f: impl FnOnce(&mut GdbCommand) -> T) -> T {
+    let bin = abs_path(bin);
+    let mut argv = vec![bin.as_str()];
+    argv.extend_from_slice(args);
+    let exec_type = ExecType::Local(&argv);
+    f(&mut GdbCommand::new(&exec_type))
+}
+
 #[test]
 fn test_local_canary() {
     let mut args = Vec::new();
     let bin = abs_path("tests/bins/test_canary");
-    let a = std::iter::repeat("A").take(200).collect::<String>();
+    let a = "A".repeat(200);
     args.push(bin.as_str());
     args.push(a.as_str());
     let result = GdbCommand::new(&ExecType::Local(&args)).bt().run();
     if result.is_err() {
-        assert!(false, "{}", result.err().unwrap());
+        panic!("{}", result.err().unwrap());
     }
     let result = result.unwrap();
-    assert_eq!(result[0].contains("__stack_chk_fail"), true);
+    assert!(result[0].contains("__stack_chk_fail"));
 }
 
 #[test]
 fn test_local_safe_func() {
     let mut args = Vec::new();
     let bin = abs_path("tests/bins/test_safeFunc");
-    let a = std::iter::repeat("A").take(200).collect::<String>();
+    let a = "A".repeat(200);
     args.push(bin.as_str());
     args.push(a.as_str());
     let result = GdbCommand::new(&ExecType::Local(&args)).bt().run();
     if result.is_err() {
-        assert!(false, "{}", result.err().unwrap());
+        panic!("{}", result.err().unwrap());
     }
     let result = result.unwrap();
-    assert_eq!(result[0].contains("__strcpy_chk"), true);
+    assert!(result[0].contains("__strcpy_chk"));
 }
 
 #[test]
@@ -51,42 +66,29 @@ fn test_struct_mapped_files() {
         .mappings()
         .run();
     if result.is_err() {
-        assert!(false, "{}", result.err().unwrap());
+        panic!("{}", result.err().unwrap());
     }
     let result = result.unwrap();
 
     let prmap = MappedFiles::from_gdb(&result[0]);
     if prmap.is_err() {
-        assert!(false, "{}", prmap.err().unwrap());
+        panic!("{}", prmap.err().unwrap());
     }
     let prmap = prmap.unwrap();
 
-    assert_eq!(
-        result[0]
-            .contains(format!("0x{:x}", prmap.files[prmap.files.len() - 1].base_address).as_str()),
-        true
-    );
-    assert_eq!(
-        result[0].contains(format!("0x{:x}", prmap.files[prmap.files.len() - 1].end).as_str()),
-        true
-    );
-    assert_eq!(
-        result[0].contains(
-            format!("0x{:x}", prmap.files[prmap.files.len() - 1].offset_in_file).as_str()
-        ),
-        true
-    );
+    assert!(result[0]
+        .contains(format!("0x{:x}", prmap.files[prmap.files.len() - 1].base_address).as_str()));
+    assert!(result[0].contains(format!("0x{:x}", prmap.files[prmap.files.len() - 1].end).as_str()));
+    assert!(result[0]
+        .contains(format!("0x{:x}", prmap.files[prmap.files.len() - 1].offset_in_file).as_str()));
     if prmap.files[prmap.files.len() - 1].name != "No_file" {
-        assert_eq!(
-            result[0].contains(&prmap.files[prmap.files.len() - 1].name.clone()),
-            true
-        );
+        assert!(result[0].contains(&prmap.files[prmap.files.len() - 1].name.clone()));
     }
 
     // Testing method 'find'
     let ffile = prmap.find(prmap.files[prmap.files.len() - 1].base_address + 2);
     if ffile.is_none() {
-        assert!(false, "File not found!");
+        panic!("File not found!");
     }
     let ffile = ffile.unwrap();
 
@@ -100,6 +102,35 @@ fn test_struct_mapped_files() {
     );
 }
 
+#[test]
+fn test_bt_with_offsets() {
+    let sttr = with_local("tests/bins/test_abort", &["A"], |cmd| {
+        cmd.r().bt_with_offsets()
+    });
+    if let Err(e) = &sttr {
+        panic!("{}", e);
+    }
+    let sttr = sttr.unwrap();
+    assert!(sttr
+        .strace
+        .iter()
+        .any(|x| matches!(&x.module, ModuleInfo::File(file) if file.name.ends_with("test_abort"))));
+}
+
+#[test]
+fn test_report() {
+    let report = with_local("tests/bins/test_abort", &["A"], |cmd| cmd.r().report());
+    if let Err(e) = &report {
+        panic!("{}", e);
+    }
+    let report = report.unwrap();
+    assert_eq!(report.stop_reason.unwrap().signal, "SIGABRT");
+    assert!(report.registers.instruction_pointer().is_some());
+    assert!(!report.mappings.files.is_empty());
+    assert!(report.disassembly.current().is_some());
+    assert_eq!(report.crash_hash, report.stacktrace.crash_hash());
+}
+
 #[test]
 fn test_stacktrace_structs() {
     let bin = abs_path("tests/bins/test_abort");
@@ -108,24 +139,18 @@ fn test_stacktrace_structs() {
         .mappings()
         .run();
     if result.is_err() {
-        assert!(false, "{}", result.err().unwrap());
+        panic!("{}", result.err().unwrap());
     }
     let result = result.unwrap();
 
     let sttr = Stacktrace::from_gdb(&result[0]);
     if sttr.is_err() {
-        assert!(false, "{}", sttr.err().unwrap());
+        panic!("{}", sttr.err().unwrap());
     }
     let mut sttr = sttr.unwrap();
 
-    assert_eq!(
-        result[0].contains(format!("{:x}", sttr.strace.last().unwrap().address).as_str()),
-        true
-    );
-    assert_eq!(
-        result[0].contains(&sttr.strace.last().unwrap().debug.file_path),
-        true
-    );
+    assert!(result[0].contains(format!("{:x}", sttr.strace.last().unwrap().address).as_str()));
+    assert!(result[0].contains(&sttr.strace.last().unwrap().debug.file_path));
 
     // Testing method 'up_stacktrace_info'
 
@@ -133,24 +158,12 @@ fn test_stacktrace_structs() {
     sttr.update_modules(&prmap);
 
     if let ModuleInfo::File(file) = &sttr.strace[sttr.strace.len() - 1].module {
-        assert_eq!(
-            result[1].contains(&format!("{:x}", file.base_address).to_string()),
-            true
-        );
-        assert_eq!(
-            result[1].contains(&format!("{:x}", file.end).to_string()),
-            true
-        );
-        assert_eq!(
-            result[1].contains(&format!("{:x}", file.offset_in_file).to_string()),
-            true
-        );
-        assert_eq!(
-            result[1].contains(&format!("{}", file.name).to_string()),
-            true
-        );
+        assert!(result[1].contains(&format!("{:x}", file.base_address).to_string()));
+        assert!(result[1].contains(&format!("{:x}", file.end).to_string()));
+        assert!(result[1].contains(&format!("{:x}", file.offset_in_file).to_string()));
+        assert!(result[1].contains(&file.name.to_string().to_string()));
     } else {
-        assert!(false, "No file...");
+        panic!("No file...");
     }
 
     let mystacktrace = &[
@@ -172,13 +185,13 @@ fn test_stacktrace_structs() {
 
     let sttr = Stacktrace::from_gdb(mystacktrace);
     if sttr.is_err() {
-        assert!(false, "{}", sttr.err().unwrap());
+        panic!("{}", sttr.err().unwrap());
     }
     let sttr = sttr.unwrap();
 
     // Eq check
     assert_eq!(sttr.strace[0], sttr.strace[5]);
-    assert_eq!(sttr.strace[1] == sttr.strace[6], false);
+    assert!(!(sttr.strace[1] == sttr.strace[6]));
     assert_eq!(sttr.strace[2], sttr.strace[7]);
     assert_eq!(sttr.strace[3], sttr.strace[8]);
     assert_eq!(sttr.strace[4], sttr.strace[9]);
@@ -194,7 +207,7 @@ fn test_stacktrace_structs() {
     });
 
     if tracehash.len() != 1 {
-        assert!(false, "Hash check fail");
+        panic!("Hash check fail");
     }
 }
 
@@ -210,10 +223,10 @@ fn test_core_canary() {
     .bt()
     .run();
     if result.is_err() {
-        assert!(false, "{}", result.err().unwrap());
+        panic!("{}", result.err().unwrap());
     }
     let result = result.unwrap();
-    assert_eq!(result[0].contains("__stack_chk_fail"), true);
+    assert!(result[0].contains("__stack_chk_fail"));
 }
 
 #[test] // To run this test: If Ubuntu 20.04 just remove ignore. Other systems: recollect the core.
@@ -228,10 +241,10 @@ fn test_core_safe_func() {
     .bt()
     .run();
     if result.is_err() {
-        assert!(false, "{}", result.err().unwrap());
+        panic!("{}", result.err().unwrap());
     }
     let result = result.unwrap();
-    assert_eq!(result[0].contains("__strcpy_chk"), true);
+    assert!(result[0].contains("__strcpy_chk"));
 }
 
 #[test] // To run this test: echo 0 | sudo tee /proc/sys/kernel/yama/ptrace_scope
@@ -250,14 +263,2378 @@ fn test_remote_unwind() {
         .bt()
         .run();
     if result.is_err() {
-        assert!(false, "{}", result.err().unwrap());
+        panic!("{}", result.err().unwrap());
     }
 
     let result = result.unwrap();
-    assert_eq!(result[0].contains("third"), true);
-    assert_eq!(result[0].contains("second"), true);
-    assert_eq!(result[0].contains("first"), true);
-    assert_eq!(result[0].contains("main"), true);
+    assert!(result[0].contains("third"));
+    assert!(result[0].contains("second"));
+    assert!(result[0].contains("first"));
+    assert!(result[0].contains("main"));
 
     child.kill().unwrap();
+    child.wait().unwrap();
+}
+
+#[test]
+fn test_clone_and_clear_commands() {
+    let bin = abs_path("tests/bins/test_abort");
+    let args = [bin.as_str(), "A"];
+    let exec_type = ExecType::Local(&args);
+    let mut template = GdbCommand::new(&exec_type);
+    template.bt();
+
+    let mut cmd = template.clone();
+    cmd.regs();
+    assert_eq!(template.commands_len(), 1);
+    assert_eq!(cmd.commands_len(), 2);
+    assert!(!has_ex(&template.command_line().unwrap(), "i r"));
+    assert!(has_ex(&cmd.command_line().unwrap(), "i r"));
+
+    cmd.clear_commands();
+    assert!(cmd.is_empty());
+    let argv = cmd.command_line().unwrap();
+    assert!(!has_ex(&argv, "bt"));
+    assert_eq!(argv[argv.len() - 2..], [bin.clone(), "A".to_string()]);
+}
+
+#[test]
+fn test_crash_location() {
+    let mappings = [
+        "process 1",
+        "Mapped address spaces:",
+        "",
+        "          Start Addr           End Addr       Size     Offset objfile",
+        "      0x555555554000     0x555555556000     0x2000        0x0 /tmp/test",
+        "      0x7ffff7dae000     0x7ffff7dd3000    0x25000        0x0 /usr/lib/libc.so.6",
+        "      0x7ffff7dd3000     0x7ffff7f4b000   0x178000    0x25000 /usr/lib/libc.so.6",
+    ]
+    .join("\n");
+    let prmap = MappedFiles::from_gdb(&mappings).unwrap();
+    let sttr = Stacktrace::from_gdb(
+        "#0  0x0000555555555169 in main () at /tmp/test.c:5:3\n#1  0x00007ffff7dd50b3 in __libc_start_main ()",
+    )
+    .unwrap();
+    assert_eq!(
+        sttr.crash_location(None, &prmap),
+        Some(CrashLocation::Source(DebugInfo {
+            file_path: "/tmp/test.c".to_string(),
+            offset_in_file: Some(5),
+            offset_in_line: 3,
+        }))
+    );
+
+    // No debug information
+    let sttr = Stacktrace::from_gdb("#0  0x00007ffff7e1a18b in raise ()").unwrap();
+    let location = CrashLocation::Module {
+        path: "/usr/lib/libc.so.6".to_string(),
+        offset: 0x6c18b,
+    };
+    assert_eq!(sttr.crash_location(None, &prmap), Some(location.clone()));
+    let mut updated = sttr.clone();
+    updated.update_modules(&prmap);
+    assert_eq!(updated.crash_location(None, &prmap), Some(location));
+    assert_eq!(
+        sttr.crash_location(None, &MappedFiles { files: Vec::new() }),
+        Some(CrashLocation::Address {
+            address: 0x7ffff7e1a18b,
+            function: Some("raise".to_string()),
+        })
+    );
+
+    // Abort is called by application
+    let sttr = Stacktrace::from_gdb(
+        "#0  0x00007ffff7e1a18b in raise ()\n#1  0x00007ffff7dd5859 in abort ()\n#2  0x0000555555555169 in main ()",
+    )
+    .unwrap();
+    let stop_reason = StopReason::from_gdb("Program received signal SIGABRT, Aborted.").unwrap();
+    assert_eq!(
+        sttr.crash_location(Some(&stop_reason), &prmap),
+        Some(CrashLocation::Module {
+            path: "/tmp/test".to_string(),
+            offset: 0x1169,
+        })
+    );
+    let stop_reason = StopReason::from_gdb("Program received signal SIGSEGV, Segmentation fault.");
+    assert!(matches!(
+        sttr.crash_location(stop_reason.as_ref(), &prmap),
+        Some(CrashLocation::Module {
+            offset: 0x6c18b,
+            ..
+        })
+    ));
+    assert_eq!(
+        Stacktrace { strace: Vec::new() }.crash_location(None, &prmap),
+        None
+    );
+}
+
+#[test]
+fn test_gdb_version() {
+    let version = GdbCommand::gdb_version();
+    if let Err(e) = &version {
+        panic!("{}", e);
+    }
+    let (major, _) = version.unwrap();
+    assert!(major >= 7);
+    assert_eq!(GdbCommand::gdb_version().unwrap().0, major);
+}
+
+#[test]
+fn test_set_charset() {
+    let result = with_local("tests/bins/test_abort", &[], |cmd| {
+        cmd.set_charset("UTF-8").ex("show charset").run()
+    });
+    if let Err(e) = &result {
+        panic!("{}", e);
+    }
+    // The host character set is "UTF-8".
+    assert!(result.unwrap()[0].contains("character set is \"UTF-8\""));
+}
+
+#[test]
+#[cfg(feature = "async")]
+fn test_run_async() {
+    let rt = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .unwrap();
+    let result = with_local("tests/bins/test_abort", &["A"], |cmd| {
+        rt.block_on(cmd.r().bt().run_async())
+    });
+    if let Err(e) = &result {
+        panic!("{}", e);
+    }
+    let result = result.unwrap();
+    assert!(result[0].contains("abort"), "{}", result[0]);
+    assert!(Stacktrace::from_gdb(&result[0]).is_ok());
+}
+
+#[test]
+fn test_disassembly_flavor() {
+    let flavor = |flavor: Option<&str>| {
+        let result = with_local("tests/bins/test_abort", &[], |cmd| {
+            cmd.disassembly_flavor(flavor)
+                .ex("show disassembly-flavor")
+                .run()
+        });
+        if let Err(e) = &result {
+            panic!("{}", e);
+        }
+        result.unwrap()[0].clone()
+    };
+    // The disassembly flavor is "intel".
+    assert!(flavor(Some("intel")).contains("\"intel\""));
+    // Gdb default is kept
+    assert!(flavor(None).contains("\"att\""));
+}
+
+#[test]
+fn test_setup_commands() {
+    let result = with_local("tests/bins/test_abort", &[], |cmd| {
+        cmd.no_default_setup()
+            .setup_command("set print pretty on")
+            .ex("show backtrace limit")
+            .ex("show print pretty")
+            .run()
+    });
+    if let Err(e) = &result {
+        panic!("{}", e);
+    }
+    let result = result.unwrap();
+    // Default "set backtrace limit 2000" is skipped
+    assert!(result[0].ends_with("is unlimited."), "{}", result[0]);
+    assert_eq!(result[1], "Pretty formatting of structures is on.");
+}
+
+#[test]
+fn test_starti() {
+    let input = std::env::temp_dir().join(format!("gdb-command-starti-{}", std::process::id()));
+    std::fs::write(&input, "starti input\n").unwrap();
+    let exec_type = ExecType::Local(&["/bin/cat"]);
+    let result = GdbCommand::new(&exec_type)
+        .stdin(Some(&input))
+        .starti()
+        .ex("c")
+        .run();
+    std::fs::remove_file(&input).unwrap();
+    if let Err(e) = &result {
+        panic!("{}", e);
+    }
+    // Program is stopped before reading stdin and run once
+    let result = result.unwrap();
+    assert!(result[0].contains("starti input"), "{}", result[0]);
+    assert!(result[0].contains("exited normally"));
+}
+
+#[test]
+fn test_delete_breakpoints() {
+    let result = with_local("tests/bins/test_abort", &["A"], |cmd| {
+        cmd.break_and_run("main")
+            .ex("b abort")
+            .disable_breakpoint(2)
+            .ex("info breakpoints")
+            .delete_breakpoints()
+            .ex("info breakpoints")
+            .c()
+            .run()
+    });
+    if let Err(e) = &result {
+        panic!("{}", e);
+    }
+    let result = result.unwrap();
+    // Num Type Disp Enb Address What
+    assert!(result[1].lines().any(|line| {
+        line.split_whitespace().take(4).collect::<Vec<&str>>() == ["2", "breakpoint", "keep", "n"]
+    }));
+    assert!(result[2].starts_with("No breakpoints"), "{}", result[2]);
+    // Program isn't stopped in abort
+    assert!(result[2].contains("SIGABRT"));
+}
+
+#[test]
+fn test_record() {
+    let result = with_local("tests/bins/test_abort", &["A"], |cmd| {
+        cmd.break_and_run("main")
+            .record()
+            .ex("info record")
+            .reverse_continue()
+            .run()
+    });
+    if let Err(e) = &result {
+        panic!("{}", e);
+    }
+    let result = result.unwrap();
+    assert!(result[1].contains("record-full"), "{}", result[1]);
+    // Execution history starts at main
+    assert!(result[2].contains("No more reverse-execution history"));
+}
+
+#[test]
+fn test_command_aborted() {
+    let result = with_local("tests/bins/test_abort", &["A"], |cmd| {
+        // Gdb exits before the last command
+        cmd.bt().python("import os\nos._exit(0)").regs().run()
+    });
+    assert!(
+        matches!(result, Err(error::Error::CommandAborted(ref cmd)) if cmd.starts_with("python")),
+        "{:?}",
+        result
+    );
+}
+
+#[test]
+fn test_parse_output() {
+    with_local("tests/bins/test_abort", &["A"], |cmd| {
+        cmd.bt().regs();
+        let argv = cmd.command_line().unwrap();
+        let marker = argv
+            .iter()
+            .find_map(|x| x.strip_prefix("p "))
+            .filter(|x| x.starts_with("\"gdb-command-"))
+            .unwrap();
+        let marker = format!("$1 = {}", marker);
+        // Marker is printed after all commands, output after it is skipped
+        let output = format!(
+            "Starting program\n{m}\n#0  main ()\n{m}\nrip 0x1\n{m}\n[Inferior 1 (process 1) killed]\n",
+            m = marker
+        );
+        assert_eq!(
+            cmd.parse_output(output.into_bytes()).unwrap(),
+            ["#0  main ()", "rip 0x1"]
+        );
+        // Gdb exited during the last command
+        let output = format!("{m}\n#0  main ()\n{m}\nrip", m = marker);
+        assert!(matches!(
+            cmd.parse_output(output.into_bytes()),
+            Err(error::Error::CommandAborted(ref command)) if command == "i r"
+        ));
+        let output = format!("{}\n#0  main ()", marker);
+        assert!(matches!(
+            cmd.parse_output(output.into_bytes()),
+            Err(error::Error::CommandAborted(ref command)) if command == "bt"
+        ));
+        assert!(matches!(
+            cmd.parse_output(b"Starting program".to_vec()),
+            Err(error::Error::CommandAborted(ref command)) if command == "bt"
+        ));
+    });
+
+    // Marker after all commands for execution types without target arguments
+    let argv = GdbCommand::new(&ExecType::Remote("1"))
+        .bt()
+        .command_line()
+        .unwrap();
+    assert_eq!(argv[argv.len() - 2], "-ex");
+    assert!(argv[argv.len() - 1].starts_with("p \"gdb-command-"));
+}
+
+#[test]
+fn test_remote_mappings() {
+    with_local("tests/bins/test_abort", &[], |cmd| {
+        cmd.remote_mappings();
+        assert_eq!(cmd.commands_len(), 2);
+        let argv = cmd.command_line().unwrap();
+        assert!(has_ex(&argv, "info proc mappings"));
+        // Syntax depends on gdb version
+        assert!(
+            has_ex(&argv, "maintenance info sections -all-objects")
+                || has_ex(&argv, "maintenance info sections ALLOBJ")
+        );
+    });
+}
+
+#[test]
+fn test_print_frame_arguments() {
+    let result = with_local("tests/bins/test_abort", &["A"], |cmd| {
+        cmd.print_frame_arguments("none")
+            .break_and_run("main")
+            .bt()
+            .run()
+    });
+    if let Err(e) = &result {
+        panic!("{}", e);
+    }
+    let result = result.unwrap();
+    // #0  main (...) at test_abort.c:N
+    assert!(result[0].contains("main (...)"), "{}", result[0]);
+    let sttr = Stacktrace::from_gdb(&result[0]).unwrap();
+    assert!(!sttr.strace[0].debug.file_path.is_empty());
+}
+
+#[test]
+fn test_disable_randomization() {
+    let randomization = |on: bool| {
+        let result = with_local("tests/bins/test_abort", &[], |cmd| {
+            cmd.disable_randomization(on)
+                .ex("show disable-randomization")
+                .run()
+        });
+        if let Err(e) = &result {
+            panic!("{}", e);
+        }
+        result.unwrap()[0].clone()
+    };
+    // Disabling randomization of debuggee's virtual address space is off.
+    assert!(randomization(false).ends_with(" is off."));
+    assert!(randomization(true).ends_with(" is on."));
+}
+
+#[test]
+fn test_address_width() {
+    let sttr = Stacktrace::from_gdb(
+        "#0  0xf7fcf569 in __kernel_vsyscall ()\n#1  0x00007ffff7dd5859 in __GI_abort ()",
+    )
+    .unwrap();
+    assert_eq!(sttr.strace[0].address_width, 32);
+    assert_eq!(sttr.strace[1].address_width, 64);
+    assert!(sttr.strace[1]
+        .to_string()
+        .starts_with("Address: 0x00007ffff7dd5859"));
+
+    let mappings = [
+        "process 1",
+        "Mapped address spaces:",
+        "",
+        "	Start Addr   End Addr       Size     Offset objfile",
+        "	 0x8048000  0x8049000     0x1000        0x0 /tmp/test32",
+        "	0xf7d8e000 0xf7dab000    0x1d000        0x0 /usr/lib32/libc.so.6",
+    ]
+    .join("\n");
+    let prmap = MappedFiles::from_gdb(&mappings).unwrap();
+    assert_eq!(prmap.files[0].address_width, 32);
+    assert!(prmap.files[0].to_string().contains("Base: 0x08048000"));
+}
+
+#[test]
+fn test_top_frame() {
+    let trace = "#0  0x00007ffff7e1a18b in raise () from /lib/libc.so.6\n#1  0x00007ffff7df9859 in abort () from /lib/libc.so.6";
+    let frame = Stacktrace::top_frame(trace).unwrap().unwrap();
+    assert_eq!(frame.address, 0x7ffff7e1a18b);
+    assert_eq!(frame, Stacktrace::from_gdb(trace).unwrap().strace[0]);
+    assert!(Stacktrace::top_frame("No stack.").unwrap().is_none());
+}
+
+#[test]
+fn test_expand_repeats() {
+    let values = expand_repeats("{1, 0 <repeats 3 times>, 2}").unwrap();
+    assert_eq!(values, vec!["1", "0", "0", "0", "2"]);
+    let values = expand_repeats("'A' <repeats 2 times>, \"B, C\", {1, 2}").unwrap();
+    assert_eq!(values, vec!["'A'", "'A'", "\"B, C\"", "{1, 2}"]);
+
+    let entry =
+        StacktraceEntry::new("#1  0x0000555555555189 in foo (s='A' <repeats 200 times>) at a.c:5")
+            .unwrap();
+    assert_eq!(entry.debug.file_path, "a.c");
+    assert_eq!(entry.debug.offset_in_file, Some(5));
+    if let ModuleInfo::Name(name) = entry.module {
+        assert!(name.contains("<repeats200times>"));
+    }
+    assert!(StacktraceEntry::new("<repeats").is_err());
+}
+
+#[test]
+#[ignore] // To run this test: If Ubuntu 20.04 just remove ignore. Other systems: recollect the core.
+fn test_core_batch() {
+    let bin = abs_path("tests/bins/test_canary");
+    let core = abs_path("tests/bins/core.test_canary");
+    let result = GdbCommand::new(&ExecType::Core {
+        target: &bin,
+        core: &core,
+    })
+    .bt()
+    .regs()
+    .run_batch(&[&core, &core]);
+    if let Err(e) = &result {
+        panic!("{}", e);
+    }
+    let result = result.unwrap();
+    assert_eq!(result.len(), 2);
+    assert_eq!(result[0].len(), 2);
+    assert!(result[0][0].contains("__stack_chk_fail"));
+    assert_eq!(result[0], result[1]);
+}
+
+#[test]
+#[ignore] // To run this test: If Ubuntu 20.04 just remove ignore. Other systems: recollect the core.
+fn test_core_batch_aborted() {
+    let bin = abs_path("tests/bins/test_canary");
+    let core = abs_path("tests/bins/core.test_canary");
+    let result = GdbCommand::new(&ExecType::Core {
+        target: &bin,
+        core: &core,
+    })
+    .bt()
+    // Gdb exits while processing the second core
+    .python("import os\nif hasattr(gdb, 'seen'):\n    os._exit(0)\ngdb.seen = True")
+    .run_batch(&[&core, &core, &core]);
+    assert!(
+        matches!(result, Err(error::Error::CommandAborted(ref cmd)) if *cmd == format!("core-file {}", core))
+    );
+}
+
+#[test]
+#[ignore] // To run this test: If Ubuntu 20.04 just remove ignore. Other systems: recollect the core.
+fn test_core_only() {
+    let core = abs_path("tests/bins/core.test_canary");
+    let result = GdbCommand::new(&ExecType::CoreOnly(&core)).bt().run();
+    if let Err(e) = &result {
+        panic!("{}", e);
+    }
+    let result = result.unwrap();
+    assert!(Stacktrace::from_gdb(&result[0]).is_ok());
+}
+
+#[test]
+fn test_batch_unsupported() {
+    let result = with_local("tests/bins/test_abort", &[], |cmd| cmd.bt().run_batch(&[]));
+    assert!(matches!(result, Err(error::Error::Unsupported(_))));
+}
+
+#[test]
+fn test_registers() {
+    let regs = [
+        "rax            0x0                 0",
+        "rsp            0x7fffffffe0d0      0x7fffffffe0d0",
+        "rip            0x555555555189      0x555555555189 <main+4>",
+        "eflags         0x246               [ IF ZF PF ]",
+    ]
+    .join("\n");
+    let regs = Registers::from_gdb(&regs).unwrap();
+    assert_eq!(regs.instruction_pointer(), Some(0x555555555189));
+    assert_eq!(regs.stack_pointer(), Some(0x7fffffffe0d0));
+    assert_eq!(regs.get("eflags"), Some(0x246));
+
+    let regs = Registers::from_gdb(
+        "eip            0x8049186           0x8049186 <main+6>\nesp            0xffffd0e0          0xffffd0e0",
+    )
+    .unwrap();
+    assert_eq!(regs.instruction_pointer(), Some(0x8049186));
+    assert_eq!(regs.stack_pointer(), Some(0xffffd0e0));
+
+    assert!(Registers::from_gdb("The program has no registers now.").is_err());
+}
+
+#[test]
+fn test_break_and_run() {
+    let result = with_local("tests/bins/test_abort", &["A"], |cmd| {
+        cmd.break_and_run("main").bt().run()
+    });
+    if let Err(e) = &result {
+        panic!("{}", e);
+    }
+    let result = result.unwrap();
+    assert!(result[0].starts_with("#0"));
+    assert!(result[0].contains("main"));
+}
+
+#[test]
+fn test_program_exited() {
+    assert_eq!(
+        program_exited("[Inferior 1 (process 42) exited normally]"),
+        Some(0)
+    );
+    assert_eq!(
+        program_exited("hello\n[Inferior 1 (process 42) exited with code 012]\n"),
+        Some(10)
+    );
+    assert_eq!(
+        program_exited("Program received signal SIGABRT, Aborted."),
+        None
+    );
+}
+
+#[test]
+fn test_stacktrace_full() {
+    let trace = [
+        "#0  __GI_raise (sig=sig@entry=6) at ../sysdeps/unix/sysv/linux/raise.c:50",
+        "        set = {__val = {0 <repeats 16 times>}}",
+        "        pid = <optimized out>",
+        "#1  0x00007ffff7dd5859 in __GI_abort () at abort.c:79",
+        "        save_stage = 1",
+        "#2  0x0000555555555189 in main ()",
+        "No symbol table info available.",
+        "#3  0x0000555555555190 in foo ()",
+        "No locals.",
+    ]
+    .join("\n");
+    let frames = Stacktrace::from_gdb_full(&trace).unwrap();
+    assert_eq!(frames.len(), 4);
+    assert_eq!(frames[0].0.debug.offset_in_file, Some(50));
+    assert_eq!(frames[0].1["set"], "{__val = {0 <repeats 16 times>}}");
+    assert_eq!(frames[0].1["pid"], "<optimized out>");
+    assert_eq!(frames[1].1["save_stage"], "1");
+    assert!(frames[2].1.is_empty());
+    assert!(frames[3].1.is_empty());
+
+    // Struct locals printed on several lines (set print pretty on)
+    let trace = [
+        "#0  0x0000555555555169 in parse (buf=0x0) at /tmp/test.c:12",
+        "        req = {",
+        "          name = 0x4006f4 \"a {b\",",
+        "          hdr = {",
+        "            len = 5",
+        "          },",
+        "          flags = 3",
+        "        }",
+        "        i = 0",
+        "#1  0x0000555555555189 in main () at /tmp/test.c:20",
+        "        r = {x = 1}",
+    ]
+    .join("\n");
+    let frames = Stacktrace::from_gdb_full(&trace).unwrap();
+    assert_eq!(frames.len(), 2);
+    assert_eq!(frames[0].1.len(), 2);
+    assert_eq!(
+        frames[0].1["req"],
+        "{name = 0x4006f4 \"a {b\", hdr = {len = 5}, flags = 3}"
+    );
+    assert_eq!(frames[0].1["i"], "0");
+    assert_eq!(frames[1].1["r"], "{x = 1}");
+}
+
+#[test]
+fn test_stacktrace_pretty() {
+    let trace = [
+        "#0  __GI_raise (sig=sig@entry=6) at ../sysdeps/unix/sysv/linux/raise.c:50",
+        "#1  0x00007ffff7dd5859 in __GI_abort () from /lib/libc.so.6",
+        "#2  0x0000555555555189 in main () at /tmp/test.c:5:3",
+    ]
+    .join("\n");
+    let mut sttr = Stacktrace::from_gdb(&trace).unwrap();
+    assert_eq!(sttr.strace[1].function_name().unwrap(), "__GI_abort");
+    let entry = StacktraceEntry::new("#0  0x0000555555555189 in concat () at a.c:1").unwrap();
+    assert_eq!(entry.function_name().unwrap(), "concat");
+    assert_eq!(
+        sttr.to_pretty_string(),
+        [
+            "#0 in __GI_raise at ../sysdeps/unix/sysv/linux/raise.c:50",
+            "#1 0x00007ffff7dd5859 in __GI_abort from /lib/libc.so.6",
+            "#2 0x0000555555555189 in main at /tmp/test.c:5:3",
+        ]
+        .join("\n")
+    );
+    // Frame numbers are kept after trimming
+    sttr.strace.remove(0);
+    assert!(sttr
+        .to_pretty_string()
+        .starts_with("#1 0x00007ffff7dd5859 in __GI_abort"));
+}
+
+#[test]
+fn test_sections() {
+    let sections = [
+        "Exec file:",
+        "    `/tmp/test', file type elf64-x86-64.",
+        " [0]      0x00000318->0x00000334 at 0x00000318: .interp ALLOC LOAD READONLY DATA HAS_CONTENTS",
+        " [13]     0x00001040->0x000011a5 at 0x00001040: .text ALLOC LOAD READONLY CODE HAS_CONTENTS",
+        " [24]     0x00004010->0x00004018 at 0x00004010: .bss ALLOC",
+    ]
+    .join("\n");
+    let sections = Sections::from_gdb(&sections).unwrap();
+    assert_eq!(sections.sections.len(), 3);
+    let text = sections.find(0x1189).unwrap();
+    assert_eq!(text.name, ".text");
+    assert!(text.flags.contains(&"CODE".to_string()));
+    assert_eq!(sections.find(0x4010).unwrap().name, ".bss");
+    assert!(sections.find(0x5000).is_none());
+    assert!(Sections::from_gdb("No sections").is_err());
+}
+
+#[test]
+fn test_stacktrace_unknown_functions() {
+    let trace = [
+        "#0  0x0000000000000000 in ?? ()",
+        "#1  0x0000555555555189 in ?? ()",
+        "#2  0x00007ffff7dd5859 in ?? () from /lib/x86_64-linux-gnu/libc.so.6",
+    ]
+    .join("\n");
+    let sttr = Stacktrace::from_gdb(&trace).unwrap();
+    assert_eq!(sttr.strace.len(), 3);
+    assert_eq!(sttr.strace[0].address, 0);
+    assert_eq!(sttr.strace[0].address_width, 64);
+    assert_eq!(sttr.strace[1].address, 0x555555555189);
+    assert!(sttr.strace[1].function_name().is_none());
+    assert!(sttr.strace[2].function_name().is_none());
+    assert_eq!(
+        sttr.strace[2].debug.file_path,
+        "/lib/x86_64-linux-gnu/libc.so.6"
+    );
+}
+
+#[test]
+fn test_raw_split() {
+    with_local("tests/bins/test_abort", &["A"], |cmd| {
+        cmd.bt().regs();
+        let result = cmd.raw_split();
+        if let Err(e) = &result {
+            panic!("{}", e);
+        }
+        let (stdout, ranges) = result.unwrap();
+        let results = cmd.run().unwrap();
+        assert_eq!(
+            String::from_utf8_lossy(&stdout[ranges[0].clone()]),
+            results[0]
+        );
+        assert!(cmd.run_joined("\n---\n").unwrap().contains("\n---\n"));
+    });
+}
+
+#[test]
+fn test_pie_module_offsets() {
+    let mappings = [
+        "process 1",
+        "Mapped address spaces:",
+        "",
+        "          Start Addr           End Addr       Size     Offset objfile",
+        "      0x555555554000     0x555555555000     0x1000        0x0 /tmp/test_pie",
+        "      0x555555555000     0x555555556000     0x1000     0x1000 /tmp/test_pie",
+        "      0x555555556000     0x555555557000     0x1000     0x2000 /tmp/test_pie",
+        "      0x555555557000     0x555555559000     0x2000     0x2000 /tmp/test_pie",
+        "      0x7ffff7dae000     0x7ffff7dd3000    0x25000        0x0 /usr/lib/libc.so.6",
+    ]
+    .join("\n");
+    let prmap = MappedFiles::from_gdb(&mappings).unwrap();
+    let trace = [
+        "#0  0x0000555555555189 in main ()",
+        "#1  0x0000555555555000 in _init ()",
+        "#2  0x0000555555558010 in data ()",
+    ]
+    .join("\n");
+    let mut sttr = Stacktrace::from_gdb(&trace).unwrap();
+    sttr.update_modules(&prmap);
+    assert_eq!(sttr.strace[0].offset(), Some(0x1189));
+    // Address at the start of segment
+    assert_eq!(sttr.strace[1].offset(), Some(0x1000));
+    assert_eq!(sttr.strace[2].offset(), Some(0x3010));
+    for entry in sttr.strace.iter() {
+        assert_eq!(entry.file_offset(&prmap), entry.offset());
+    }
+
+    // File offset doesn't need modules to be updated
+    let sttr = Stacktrace::from_gdb(&trace).unwrap();
+    assert!(sttr.strace.iter().all(|x| x.offset().is_none()));
+    assert_eq!(sttr.strace[0].file_offset(&prmap), Some(0x1189));
+    assert_eq!(sttr.strace[1].file_offset(&prmap), Some(0x1000));
+    assert_eq!(sttr.strace[2].file_offset(&prmap), Some(0x3010));
+
+    // Crashing module
+    assert_eq!(sttr.crashing_module(), None);
+    let trace = [
+        "#0  foo (x=1) at /tmp/test.c:3",
+        "#1  0x00007ffff7dae010 in qsort ()",
+        "#2  0x0000555555555189 in main ()",
+    ]
+    .join("\n");
+    let mut sttr = Stacktrace::from_gdb(&trace).unwrap();
+    sttr.update_modules(&prmap);
+    assert_eq!(sttr.crashing_module().as_deref(), Some("libc.so.6"));
+    sttr.strace.remove(1);
+    assert_eq!(sttr.crashing_module().as_deref(), Some("test_pie"));
+    let entry = StacktraceEntry::new("#0  0x0000000000001000 in foo ()").unwrap();
+    assert!(entry.file_offset(&prmap).is_none());
+}
+
+#[test]
+#[cfg(feature = "symbolize")]
+fn test_symbolize() {
+    let bin = abs_path("tests/bins/test_abort");
+    let mappings = [
+        "process 1".to_string(),
+        "Mapped address spaces:".to_string(),
+        "".to_string(),
+        "          Start Addr           End Addr       Size     Offset objfile".to_string(),
+        format!(
+            "      0x555555554000     0x555555555000     0x1000        0x0 {}",
+            bin
+        ),
+        "      0x7ffff7dae000     0x7ffff7dd3000    0x25000        0x0 /nonexistent/libc.so.6"
+            .to_string(),
+    ]
+    .join("\n");
+    let prmap = MappedFiles::from_gdb(&mappings).unwrap();
+    // main is at 0x6da in test_abort
+    let mut sttr = Stacktrace::from_gdb(
+        "#0  0x00007ffff7dae100 in raise ()\n#1  0x00005555555546e0 in main ()",
+    )
+    .unwrap();
+    sttr.symbolize(&prmap);
+    assert!(sttr.strace[0].debug.offset_in_file.is_none());
+    assert!(sttr.strace[1].debug.offset_in_file.is_some());
+    assert!(sttr.strace[1].debug.file_path.ends_with(".c"));
+}
+
+#[test]
+fn test_run_streaming() {
+    with_local("tests/bins/test_abort", &["A"], |cmd| {
+        cmd.bt().regs();
+        let mut lines: Vec<Vec<String>> = vec![Vec::new(), Vec::new(), Vec::new()];
+        let result = cmd.run_streaming(|i, line| lines[i].push(line.to_string()));
+        if let Err(e) = &result {
+            panic!("{}", e);
+        }
+        let results = cmd.run().unwrap();
+        assert_eq!(lines[0].join("\n").trim(), results[0]);
+        assert_eq!(lines[1].join("\n").trim(), results[1]);
+    });
+}
+
+#[test]
+fn test_stacktrace_pagination() {
+    let trace = [
+        "#0  0x00007ffff7e1a18b in raise () from /lib/libc.so.6",
+        "--Type <RET> for more, q to quit, c to continue without paging--#1  0x00007ffff7df9859 in abort () from /lib/libc.so.6",
+        "---Type <return> to continue, or q <return> to quit---",
+        "#2  0x0000555555555189 in main ()",
+    ]
+    .join("\n");
+    let sttr = Stacktrace::from_gdb(&trace).unwrap();
+    assert_eq!(sttr.strace.len(), 3);
+    assert_eq!(sttr.strace[1].address, 0x7ffff7df9859);
+    assert_eq!(sttr.strace[2].function_name().unwrap(), "main");
+}
+
+#[test]
+fn test_stop_reason() {
+    let reason = StopReason::from_gdb(
+        "[New Thread 0x7ffff7a4e640 (LWP 2)]\n\nThread 7 \"worker\" received signal SIGABRT, Aborted.\n[Switching to Thread 0x7ffff7a4e640 (LWP 2)]",
+    )
+    .unwrap();
+    assert_eq!(reason.signal, "SIGABRT");
+    assert_eq!(reason.description, "Aborted");
+    assert_eq!(reason.thread_id.as_deref(), Some("7"));
+    assert_eq!(reason.thread_name.as_deref(), Some("worker"));
+
+    let reason =
+        StopReason::from_gdb("\nProgram received signal SIGSEGV, Segmentation fault.\n").unwrap();
+    assert_eq!(reason.signal, "SIGSEGV");
+    assert_eq!(reason.description, "Segmentation fault");
+    assert!(reason.thread_id.is_none());
+
+    let reason = StopReason::from_gdb("Program terminated with signal SIGABRT, Aborted.").unwrap();
+    assert_eq!(reason.signal, "SIGABRT");
+    assert!(StopReason::from_gdb("[Inferior 1 (process 1) exited normally]").is_none());
+}
+
+#[test]
+fn test_cwd() {
+    let dir = std::path::PathBuf::from(abs_path("tests/bins"));
+    let result = with_local("/bin/ls", &["core.test_canary"], |cmd| cmd.cwd(&dir).raw());
+    if let Err(e) = &result {
+        panic!("{}", e);
+    }
+    let output = String::from_utf8_lossy(&result.unwrap()).to_string();
+    assert!(!output.contains("No such file"));
+    assert_eq!(program_exited(&output), Some(0));
+}
+
+#[test]
+fn test_line_info() {
+    let info = LineInfo::from_gdb(
+        "Line 5 of \"test.c\" starts at address 0x1149 <main> and ends at 0x1151 <main+8>.",
+    )
+    .unwrap();
+    assert_eq!(
+        info,
+        LineInfo {
+            file: "test.c".to_string(),
+            line: 5,
+            start: 0x1149,
+            end: 0x1151
+        }
+    );
+    let info = LineInfo::from_gdb(
+        "Line 3 of \"/tmp/test.c\" is at address 0x1149 <main> but contains no code.",
+    )
+    .unwrap();
+    assert_eq!(info.line, 3);
+    assert_eq!(info.start, info.end);
+    assert!(matches!(
+        LineInfo::from_gdb("No line number information available for address 0x1149"),
+        Err(error::Error::LineInfoParse(_))
+    ));
+}
+
+#[test]
+fn test_stacktrace_merge() {
+    let asan = Stacktrace::from_gdb(
+        "#0 0x4f2d3a in foo /tmp/test.c:10:5\n#1 0x4f2e10 in main /tmp/test.c:20:3",
+    )
+    .unwrap();
+    let gdb = Stacktrace::from_gdb(
+        "#0  0x00000000004f2d3a in foo ()\n#1  0x00000000004f2e10 in main () at test.c:21\n#2  0x00007ffff7dd50b3 in __libc_start_main ()",
+    )
+    .unwrap();
+    let merged = Stacktrace::merge(&asan, &gdb);
+    assert_eq!(merged.strace.len(), 3);
+    assert_eq!(merged.strace[0].address, 0x4f2d3a);
+    assert_eq!(merged.strace[0].debug, asan.strace[0].debug);
+    // Gdb debug info is preferred
+    assert_eq!(merged.strace[1].debug, gdb.strace[1].debug);
+    assert!(merged.strace[2].debug.offset_in_file.is_none());
+}
+
+#[test]
+fn test_proc_info() {
+    let output = "process 1234
+cmdline = '/usr/bin/ls -la'
+cwd = '/root'
+exe = '/usr/bin/ls'
+Mapped address spaces:
+
+          Start Addr           End Addr       Size     Offset objfile
+      0x555555554000     0x555555558000     0x4000        0x0 /usr/bin/ls
+      0x555555558000     0x55555556d000    0x15000     0x4000 /usr/bin/ls
+      0x7ffff7fc3000     0x7ffff7fc7000     0x4000        0x0 [vvar]
+Name:\tls
+State:\tt (tracing stop)
+Pid:\t1234";
+    let info = ProcInfo::from_gdb(output).unwrap();
+    assert_eq!(info.cmdline.as_deref(), Some("/usr/bin/ls -la"));
+    assert_eq!(info.cwd.as_deref(), Some("/root"));
+    assert_eq!(info.exe.as_deref(), Some("/usr/bin/ls"));
+    let mappings = info.mappings.unwrap();
+    assert_eq!(mappings.files.len(), 3);
+    assert_eq!(mappings.files[1].offset_in_file, 0x4000);
+    assert_eq!(info.status["Name"], "ls");
+    assert_eq!(info.status["State"], "t (tracing stop)");
+    assert_eq!(info.status.len(), 3);
+
+    assert!(matches!(
+        ProcInfo::from_gdb("No current process"),
+        Err(error::Error::ProcInfoParse(_))
+    ));
+}
+
+#[test]
+fn test_fault_address() {
+    assert_eq!(fault_address("$1 = (void *) 0x0"), Some(0));
+    assert_eq!(
+        fault_address("$3 = (void *) 0x7ffff7ff1000\n"),
+        Some(0x7ffff7ff1000)
+    );
+    assert_eq!(fault_address("$1 = void"), None);
+    assert_eq!(fault_address("There is no member named _sifields."), None);
+}
+
+#[test]
+fn test_stacktrace_corrupt() {
+    let trace = "#0  0x0000555555555169 in main () at /tmp/test.c:5
+#1  0x4141414141414141 in ?? ()
+Backtrace stopped: previous frame inner to this frame (corrupt stack?)";
+    let checked = Stacktrace::from_gdb_checked(trace).unwrap();
+    assert_eq!(checked.stacktrace.strace.len(), 2);
+    assert!(checked.truncated);
+    assert!(checked.corrupt);
+    assert_eq!(checked.stacktrace, Stacktrace::from_gdb(trace).unwrap());
+
+    let checked = Stacktrace::from_gdb_checked("#0  0x0000555555555169 in main ()").unwrap();
+    assert!(!checked.truncated);
+    assert!(!checked.corrupt);
+}
+
+#[test]
+fn test_scheduler_locking() {
+    with_local("tests/bins/test_abort", &[], |cmd| {
+        assert!(cmd.scheduler_locking("step").is_ok());
+        assert!(has_ex(
+            &cmd.command_line().unwrap(),
+            "set scheduler-locking step"
+        ));
+        assert!(matches!(
+            cmd.scheduler_locking("always"),
+            Err(error::Error::Unsupported(_))
+        ));
+    });
+}
+
+#[test]
+fn test_handle_signal() {
+    with_local("tests/bins/test_abort", &[], |cmd| {
+        assert!(cmd
+            .handle_signal("SIGPIPE", &["nostop", "noprint", "pass"])
+            .is_ok());
+        assert!(has_ex(
+            &cmd.command_line().unwrap(),
+            "handle SIGPIPE nostop noprint pass"
+        ));
+        assert!(cmd.is_empty());
+        assert!(matches!(
+            cmd.handle_signal("SIGPIPE", &["skip"]),
+            Err(error::Error::Unsupported(_))
+        ));
+        assert!(matches!(
+            cmd.handle_signal("SIGPIPE; shell id", &["stop"]),
+            Err(error::Error::Unsupported(_))
+        ));
+    });
+}
+
+#[test]
+fn test_stepping() {
+    with_local("tests/bins/test_abort", &[], |cmd| {
+        cmd.next(2).stepi();
+        assert_eq!(cmd.commands_len(), 2);
+        assert!(!has_ex(&cmd.command_line().unwrap(), "list *$pc"));
+        cmd.list_after_step().step(1).nexti();
+        assert_eq!(cmd.commands_len(), 6);
+        let argv = cmd.command_line().unwrap();
+        assert_eq!(argv.iter().filter(|x| *x == "list *$pc").count(), 2);
+        assert!(has_ex(&argv, "n 2") && has_ex(&argv, "s 1"));
+    });
+}
+
+#[test]
+fn test_mappings_from_sections() {
+    let sections = "Exec file: `/tmp/test', file type elf64-x86-64.
+ [0]      0x555555554318->0x555555554334 at 0x00000318: .interp ALLOC LOAD READONLY DATA HAS_CONTENTS
+ [1]      0x555555555000->0x555555555200 at 0x00001000: .text ALLOC LOAD READONLY CODE HAS_CONTENTS
+ [2]      0x00000000->0x0000002b at 0x00003010: .comment READONLY HAS_CONTENTS
+Object file: /lib/x86_64-linux-gnu/libc.so.6
+ [3]      0x7ffff7dd5000->0x7ffff7f4d000 at 0x00028000: .text ALLOC LOAD READONLY CODE HAS_CONTENTS";
+    let mappings = MappedFiles::from_remote("Not supported on this target.", sections).unwrap();
+    assert_eq!(mappings.files.len(), 3);
+    assert_eq!(mappings.files[1].name, "/tmp/test");
+    assert_eq!(mappings.files[1].offset_in_file, 0x1000);
+    assert_eq!(mappings.files[2].name, "/lib/x86_64-linux-gnu/libc.so.6");
+    assert_eq!(mappings.files[2].address_width, 64);
+    assert_eq!(
+        mappings.find(0x7ffff7dd5010).unwrap().name,
+        "/lib/x86_64-linux-gnu/libc.so.6"
+    );
+    assert!(MappedFiles::from_sections("No sections").is_err());
+
+    // Core without file notes
+    let output = "Can not get mappings. Could not read `/proc/1234/maps'";
+    assert!(matches!(
+        MappedFiles::from_gdb(output),
+        Err(error::Error::MappedFilesParse(msg)) if msg.contains("unavailable")
+    ));
+    let sections = format!(
+        "{}\nCore file: `/tmp/core', file type elf64-x86-64.
+ [0]      0x555555557000->0x555555558000 at 0x00002000: load1 ALLOC LOAD HAS_CONTENTS",
+        sections
+    );
+    let mappings = MappedFiles::from_remote(output, &sections).unwrap();
+    assert_eq!(mappings.files.len(), 3);
+    assert!(mappings.find(0x555555557010).is_none());
+
+    // Warning after header
+    let output = "process 1234
+Mapped address spaces:
+
+          Start Addr           End Addr       Size     Offset objfile
+warning: unable to open /proc file '/proc/1234/maps'
+      0x555555554000     0x555555555000     0x1000        0x0 /tmp/test";
+    assert!(matches!(
+        MappedFiles::from_gdb(output),
+        Err(error::Error::MappedFilesParse(_))
+    ));
+    let mappings = MappedFiles::from_remote(output, &sections).unwrap();
+    assert_eq!(mappings.files.len(), 3);
+}
+
+#[test]
+fn test_symbol_info() {
+    let info =
+        SymbolInfo::from_gdb("printf + 16 in section .text of /lib/x86_64-linux-gnu/libc.so.6")
+            .unwrap();
+    assert_eq!(info.symbol, "printf");
+    assert_eq!(info.offset, 16);
+    assert_eq!(info.section, ".text");
+    assert_eq!(
+        info.module.as_deref(),
+        Some("/lib/x86_64-linux-gnu/libc.so.6")
+    );
+
+    let info = SymbolInfo::from_gdb("main in section .text\n").unwrap();
+    assert_eq!(info.symbol, "main");
+    assert_eq!(info.offset, 0);
+    assert_eq!(info.module, None);
+
+    assert!(matches!(
+        SymbolInfo::from_gdb("No symbol matches 0x1234."),
+        Err(error::Error::SymbolInfoParse(_))
+    ));
+}
+
+#[test]
+fn test_catch() {
+    assert_eq!(
+        CatchEvent::Syscall(Some("write")).to_string(),
+        "catch syscall write"
+    );
+    assert_eq!(
+        CatchEvent::Signal(Some("SIGSEGV")).to_string(),
+        "catch signal SIGSEGV"
+    );
+    assert_eq!(CatchEvent::Throw.to_string(), "catch throw");
+
+    let output = "Catchpoint 1 (syscall 'write' [1])\n\nCatchpoint 1 (call to syscall write), 0x00007ffff7e9a297 in write () from /lib/libc.so.6\n";
+    assert_eq!(
+        catchpoint_hit(output).as_deref(),
+        Some("Catchpoint 1 (call to syscall write), 0x00007ffff7e9a297 in write () from /lib/libc.so.6")
+    );
+    assert_eq!(
+        catchpoint_hit("[Inferior 1 (process 42) exited normally]"),
+        None
+    );
+}
+
+#[test]
+fn test_mappings_with_spaces() {
+    let mappings = [
+        "process 1",
+        "Mapped address spaces:",
+        "",
+        "          Start Addr           End Addr       Size     Offset objfile",
+        "      0x555555554000     0x555555555000     0x1000        0x0 /home/My App/bin",
+        "      0x555555555000     0x555555556000     0x1000     0x1000 /home/My  App/bin",
+        "      0x7ffffffde000     0x7ffffffff000    0x21000        0x0 [stack]",
+        "      0x7ffff7fc3000     0x7ffff7fc7000     0x4000        0x0 ",
+    ]
+    .join("\n");
+    let prmap = MappedFiles::from_gdb(&mappings).unwrap();
+    assert_eq!(prmap.files[0].name, "/home/My App/bin");
+    assert_eq!(prmap.files[1].name, "/home/My  App/bin");
+    assert_eq!(prmap.files[1].offset_in_file, 0x1000);
+    assert_eq!(prmap.files[2].name, "[stack]");
+    assert_eq!(prmap.files[3].name, "");
+
+    // gdb 12+ prints permissions column
+    let mappings = [
+        "process 1",
+        "Mapped address spaces:",
+        "",
+        "          Start Addr           End Addr       Size     Offset  Perms  objfile",
+        "      0x555555554000     0x555555555000     0x1000        0x0  r--p   /home/My App/bin",
+        "      0x555555555000     0x555555556000     0x1000     0x1000  r-xp   /home/My App/bin",
+    ]
+    .join("\n");
+    let prmap = MappedFiles::from_gdb(&mappings).unwrap();
+    assert_eq!(prmap.files[1].name, "/home/My App/bin");
+    assert_eq!(prmap.files[1].offset_in_file, 0x1000);
+    assert_eq!(prmap.files[1].perms, "r-xp");
+}
+
+#[test]
+fn test_mapped_regions() {
+    let mappings = [
+        "process 1",
+        "Mapped address spaces:",
+        "",
+        "          Start Addr           End Addr       Size     Offset  Perms  objfile",
+        "      0x555555554000     0x555555555000     0x1000        0x0  r--p   /tmp/bin",
+        "      0x555555555000     0x555555556000     0x1000     0x1000  r-xp   /tmp/bin",
+        "      0x555555557000     0x555555558000     0x1000     0x3000  rw-p   /tmp/bin",
+        "      0x7ffffffde000     0x7ffffffff000    0x21000        0x0  rw-p   [stack]",
+    ]
+    .join("\n");
+    let prmap = MappedFiles::from_gdb(&mappings).unwrap();
+    let exec = prmap.executable_regions().collect::<Vec<&File>>();
+    assert_eq!(exec.len(), 1);
+    assert_eq!(exec[0].base_address, 0x555555555000);
+    let writable = prmap
+        .writable_regions()
+        .map(|x| x.name.as_str())
+        .collect::<Vec<&str>>();
+    assert_eq!(writable, vec!["/tmp/bin", "[stack]"]);
+
+    // No permissions column before gdb 12
+    let mappings = [
+        "process 1",
+        "Mapped address spaces:",
+        "",
+        "          Start Addr           End Addr       Size     Offset objfile",
+        "      0x555555555000     0x555555556000     0x1000     0x1000 /tmp/bin",
+        "      0x555555557000     0x555555558000     0x1000     0x3000 /tmp/bin",
+    ]
+    .join("\n");
+    let prmap = MappedFiles::from_gdb(&mappings).unwrap();
+    assert!(prmap.files[0].perms.is_empty());
+    assert_eq!(prmap.executable_regions().count(), 0);
+    assert_eq!(prmap.writable_regions().count(), 0);
+}
+
+#[test]
+fn test_invalid_path() {
+    let bin = abs_path("tests/bins/test_abort");
+    let stdin = std::path::PathBuf::from("/tmp/in'; rm -rf ~");
+    let result = GdbCommand::new(&ExecType::Local(&[&bin]))
+        .stdin(Some(&stdin))
+        .r()
+        .bt()
+        .run();
+    assert!(matches!(result, Err(error::Error::InvalidPath(_))));
+
+    // Spaces and shell metacharacters are quoted
+    let stdin = std::path::PathBuf::from("/tmp/my dir/in; rm -rf ~");
+    let argv = GdbCommand::new(&ExecType::Local(&[&bin]))
+        .stdin(Some(&stdin))
+        .r()
+        .command_line()
+        .unwrap();
+    assert!(has_ex(&argv, "r < '/tmp/my dir/in; rm -rf ~'"));
+
+    let cwd = std::path::PathBuf::from("/tmp\nshell id");
+    let result = with_local("tests/bins/test_abort", &[], |cmd| cmd.cwd(&cwd).bt().run());
+    assert!(matches!(result, Err(error::Error::InvalidPath(_))));
+
+    let log = std::path::PathBuf::from("/tmp/gdb.log\nshell id");
+    let result = with_local("tests/bins/test_abort", &[], |cmd| {
+        cmd.log_to(&log).bt().run()
+    });
+    assert!(matches!(result, Err(error::Error::InvalidPath(_))));
+
+    let dump = std::path::PathBuf::from("/tmp/my dump.bin");
+    let result = with_local("tests/bins/test_abort", &[], |cmd| {
+        cmd.dump_memory(&dump, "$sp", "$sp+16").run()
+    });
+    assert!(matches!(result, Err(error::Error::InvalidPath(_))));
+}
+
+#[test]
+fn test_build_ids() {
+    let output = "/tmp/my app/test\t1a2b3c4d\n/lib/x86_64-linux-gnu/libc.so.6\t490fef8403240c91833978d494d39e537409b92e\nsystem-supplied DSO at 0x7ffff7fc1000\t\n/tmp/libnoid.so\t";
+    let ids = build_ids(output);
+    assert_eq!(ids.len(), 2);
+    assert_eq!(ids["/tmp/my app/test"], "1a2b3c4d");
+    assert_eq!(
+        ids["/lib/x86_64-linux-gnu/libc.so.6"],
+        "490fef8403240c91833978d494d39e537409b92e"
+    );
+}
+
+#[test]
+fn test_shared_libraries() {
+    let output = "From                To                  Syms Read   Shared Object Library
+0x00007ffff7fc5090  0x00007ffff7fee315  Yes         /lib64/ld-linux-x86-64.so.2
+0x00007ffff7dad700  0x00007ffff7f3f93d  Yes (*)     /lib/x86_64-linux-gnu/libc.so.6
+                                        No          /opt/my lib/libfoo.so
+(*): Shared library is missing debugging information.";
+    let libs = SharedLibraries::from_gdb(output).unwrap();
+    assert_eq!(libs.libraries.len(), 3);
+    assert_eq!(libs.libraries[1].from, 0x7ffff7dad700);
+    assert_eq!(libs.libraries[1].to, 0x7ffff7f3f93d);
+    assert!(libs.libraries[1].syms_read);
+    assert!(!libs.libraries[1].debug_info);
+    assert!(libs.libraries[0].debug_info);
+    assert_eq!(libs.libraries[1].name, "/lib/x86_64-linux-gnu/libc.so.6");
+    assert!(!libs.libraries[2].syms_read);
+    assert_eq!(libs.libraries[2].name, "/opt/my lib/libfoo.so");
+    assert_eq!(
+        libs.find(0x7ffff7fc6000).unwrap().name,
+        "/lib64/ld-linux-x86-64.so.2"
+    );
+
+    let libs = SharedLibraries::from_gdb("No shared libraries loaded at this time.").unwrap();
+    assert!(libs.libraries.is_empty());
+    assert!(SharedLibraries::from_gdb("No symbol table is loaded.").is_err());
+}
+
+#[test]
+fn test_on_crash() {
+    let bin = abs_path("tests/bins/test_abort");
+    let args = [bin.as_str(), "A"];
+    let exec_type = ExecType::Local(&args);
+    let mut cmd = GdbCommand::new(&exec_type);
+    cmd.r().bt().on_crash(|cmd| {
+        cmd.regs().ex("p 'x'");
+    });
+    assert_eq!(cmd.commands_len(), 3);
+    let argv = cmd.command_line().unwrap();
+    assert!(has_ex(&argv, "bt"));
+    let guarded = argv
+        .iter()
+        .filter(|x| x.contains("$_siginfo.si_signo"))
+        .collect::<Vec<&String>>();
+    assert_eq!(guarded.len(), 2);
+    assert!(guarded[0].contains("gdb.execute('i r')"));
+    assert!(guarded[1].contains(r"gdb.execute('p \\'x\\'')"));
+
+    // Commands are skipped at breakpoint (SIGTRAP) and executed after crash
+    let result = GdbCommand::new(&exec_type)
+        .break_and_run("main")
+        .on_crash(|cmd| {
+            cmd.bt();
+        })
+        .c()
+        .on_crash(|cmd| {
+            cmd.bt();
+        })
+        .run();
+    if let Err(e) = &result {
+        panic!("{}", e);
+    }
+    let result = result.unwrap();
+    assert!(result[0].is_empty());
+    assert!(result[1].starts_with("#0"));
+}
+
+#[test]
+fn test_stdin_lines() {
+    let result = with_local("/bin/cat", &[], |cmd| {
+        cmd.stdin_lines(&["first line", "second line"]).raw()
+    });
+    if let Err(e) = &result {
+        panic!("{}", e);
+    }
+    let output = String::from_utf8_lossy(&result.unwrap()).to_string();
+    assert!(output.contains("first line\nsecond line\n"));
+    assert_eq!(program_exited(&output), Some(0));
+}
+
+#[test]
+fn test_trim_to_signal() {
+    let trace = [
+        "#0  __pthread_kill_implementation (no_tid=0, signo=6, threadid=140737351481152) at ./nptl/pthread_kill.c:44",
+        "#1  0x00007ffff7e2a76f in __pthread_kill_internal (signo=6, threadid=<optimized out>) at ./nptl/pthread_kill.c:78",
+        "#2  0x00007ffff7ddb4f6 in __GI_raise (sig=sig@entry=6) at ../sysdeps/posix/raise.c:26",
+        "#3  0x00007ffff7dc57f3 in __GI_abort () at ./stdlib/abort.c:79",
+        "#4  0x0000555555555169 in parse (data=0x0) at /tmp/test.c:5",
+        "#5  0x0000555555555189 in main () at /tmp/test.c:10",
+    ]
+    .join("\n");
+    let mut sttr = Stacktrace::from_gdb(&trace).unwrap();
+    sttr.trim_to_signal();
+    assert_eq!(sttr.strace.len(), 2);
+    assert_eq!(sttr.strace[0].function_name().as_deref(), Some("parse"));
+
+    let mut sttr = Stacktrace::from_gdb(&trace).unwrap();
+    sttr.trim_frames(&["__pthread_kill_*"]);
+    assert_eq!(sttr.strace.len(), 4);
+
+    // All frames are runtime ones
+    let mut sttr = Stacktrace::from_gdb("#0  0x00007ffff7dc57f3 in abort ()").unwrap();
+    sttr.trim_to_signal();
+    assert_eq!(sttr.strace.len(), 1);
+}
+
+#[test]
+fn test_commands_len() {
+    with_local("tests/bins/test_abort", &[], |cmd| {
+        cmd.r();
+        assert!(cmd.is_empty());
+        cmd.bt().regs().c();
+        assert_eq!(cmd.commands_len(), 2);
+        assert!(!cmd.is_empty());
+    });
+}
+
+#[test]
+fn test_frame_arguments() {
+    let entry = StacktraceEntry::new(
+        "#1  0x0000555555555169 in parse (data=0x4006f4 \"a, (b)\", len=10, cfg=...) at /tmp/test.c:5",
+    )
+    .unwrap();
+    assert_eq!(entry.function_name().as_deref(), Some("parse"));
+    assert_eq!(
+        entry.arguments,
+        vec![
+            ("data".to_string(), "0x4006f4 \"a, (b)\"".to_string()),
+            ("len".to_string(), "10".to_string()),
+            ("cfg".to_string(), "...".to_string()),
+        ]
+    );
+
+    let entry = StacktraceEntry::new(
+        "#0  __GI_raise (sig=sig@entry=6) at ../sysdeps/unix/sysv/linux/raise.c:50",
+    )
+    .unwrap();
+    assert_eq!(
+        entry.arguments,
+        vec![("sig".to_string(), "sig@entry=6".to_string())]
+    );
+
+    let entry = StacktraceEntry::new(
+        "#2  0x0000555555555189 in std::map<int, int>::at (this=0x7fffffffe0a0, k=@0x7fffffffe09c: 1) at map.h:10",
+    )
+    .unwrap();
+    assert_eq!(entry.arguments.len(), 2);
+    assert_eq!(entry.arguments[1].1, "@0x7fffffffe09c: 1");
+
+    let entry = StacktraceEntry::new("#3  0x0000555555555189 in main ()").unwrap();
+    assert!(entry.arguments.is_empty());
+}
+
+#[test]
+fn test_crash_hash() {
+    let first = Stacktrace::from_gdb(
+        "#0  0x0000555555555169 in main () at /tmp/test.c:5\n#1  0x00007ffff7dd50b3 in __libc_start_main ()",
+    )
+    .unwrap();
+    let second = Stacktrace::from_gdb(
+        "#0  0x0000555555555169 in main (argc=2) at /tmp/test.c:5\n#1  0x00007ffff7dd50b3 in __libc_start_main ()",
+    )
+    .unwrap();
+    let other = Stacktrace::from_gdb("#0  0x0000555555555169 in main () at /tmp/test.c:6").unwrap();
+    assert_eq!(first.crash_hash(), second.crash_hash());
+    assert_ne!(first.crash_hash(), other.crash_hash());
+    // Hash is stable and may be stored between runs
+    assert_eq!(first.crash_hash(), 0xdcc9f4d14a3f18d6);
+    assert!(dedup_cores(&[], "/bin/true").unwrap().is_empty());
+}
+
+#[test]
+#[ignore] // To run this test: If Ubuntu 20.04 just remove ignore. Other systems: recollect the core.
+fn test_dedup_cores() {
+    let bin = abs_path("tests/bins/test_canary");
+    let core = abs_path("tests/bins/core.test_canary");
+    let groups = dedup_cores(&[&core, &core], &bin).unwrap();
+    assert_eq!(groups.len(), 1);
+    assert_eq!(groups.values().next().unwrap().len(), 2);
+
+    // Cores after a broken one are not dropped silently
+    let missing = abs_path("tests/bins/core.missing");
+    assert!(matches!(
+        dedup_cores(&[&core, &missing, &core], &bin),
+        Err(error::Error::NoFile(_))
+    ));
+    let broken = std::env::temp_dir().join(format!("core.broken.{}", std::process::id()));
+    std::fs::write(&broken, b"\x7fELF\x02\x01\x01\x00").unwrap();
+    let result = dedup_cores(&[&core, broken.to_str().unwrap(), &core], &bin);
+    std::fs::remove_file(&broken).unwrap();
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_crlf_output() {
+    let mappings = "process 1\r\nMapped address spaces:\r\n\r\n          Start Addr           End Addr       Size     Offset objfile\r\n      0x555555554000     0x555555555000     0x1000        0x0 /tmp/test\r\n      0x7ffff7dae000     0x7ffff7dd3000    0x25000        0x0 /usr/lib/libc.so.6\r\n";
+    let prmap = MappedFiles::from_gdb(mappings).unwrap();
+    assert_eq!(prmap.files[0].name, "/tmp/test");
+    assert_eq!(prmap.files[1].name, "/usr/lib/libc.so.6");
+
+    let sttr = Stacktrace::from_gdb(
+        "#0  0x0000555555555169 in main () at /tmp/test.c:5\r\n#1  0x00007ffff7dd50b3 in __libc_start_main () from /usr/lib/libc.so.6\r\n",
+    )
+    .unwrap();
+    assert_eq!(sttr.strace[0].function_name().as_deref(), Some("main"));
+    assert_eq!(sttr.strace[0].debug.file_path, "/tmp/test.c");
+    assert_eq!(sttr.strace[1].debug.file_path, "/usr/lib/libc.so.6");
+
+    let reason =
+        StopReason::from_gdb("\r\nProgram received signal SIGSEGV, Segmentation fault.\r\n")
+            .unwrap();
+    assert_eq!(reason.description, "Segmentation fault");
+
+    let libs = SharedLibraries::from_gdb(
+        "0x00007ffff7dad700  0x00007ffff7f3f93d  Yes         /lib/libc.so.6\r\n",
+    )
+    .unwrap();
+    assert_eq!(libs.libraries[0].name, "/lib/libc.so.6");
+}
+
+#[test]
+fn test_core_cmdline() {
+    let output = "[New LWP 1234]\nCore was generated by `./prog --flag input'.\nProgram terminated with signal SIGSEGV, Segmentation fault.\n";
+    assert_eq!(core_cmdline(output).as_deref(), Some("./prog --flag input"));
+    assert_eq!(
+        core_args(output).unwrap(),
+        vec!["./prog", "--flag", "input"]
+    );
+    assert_eq!(core_cmdline("Program received signal SIGSEGV"), None);
+}
+
+#[test]
+fn test_max_value_size() {
+    with_local("tests/bins/test_abort", &[], |cmd| {
+        cmd.max_value_size(Some(1));
+        assert!(has_ex(
+            &cmd.command_line().unwrap(),
+            "set max-value-size 16"
+        ));
+        cmd.max_value_size(None);
+        let argv = cmd.command_line().unwrap();
+        assert!(has_ex(&argv, "set max-value-size unlimited"));
+        assert!(!has_ex(&argv, "set max-value-size 16"));
+    });
+}
+
+#[test]
+fn test_run_partial() {
+    let result = with_local("tests/bins/test_abort", &["A"], |cmd| {
+        cmd.r().bt().regs().run_partial()
+    });
+    if let Err(e) = &result {
+        panic!("{}", e);
+    }
+    let result = result.unwrap();
+    assert_eq!(result.results.len(), 2);
+    assert!(result.missing.is_empty());
+}
+
+#[test]
+fn test_app_frame() {
+    let trace = [
+        "#0  0x00007ffff7e2a76f in __pthread_kill_internal (signo=6) at ./nptl/pthread_kill.c:78",
+        "#1  0x00007ffff7dc57f3 in abort () from /lib/x86_64-linux-gnu/libc.so.6",
+        "#2  0x00007ffff7dc5700 in ?? ()",
+        "#3  0x0000555555555169 in parse (data=0x0) at /home/user/app/parse.c:5",
+        "#4  0x0000555555555189 in main () at /home/user/app/main.c:10",
+    ]
+    .join("\n");
+    let sttr = Stacktrace::from_gdb(&trace).unwrap();
+    let denylist = [
+        regex::Regex::new(r"^\./nptl/").unwrap(),
+        regex::Regex::new(r"^/lib/").unwrap(),
+    ];
+    let frame = sttr.app_frame(&denylist).unwrap();
+    assert_eq!(frame.debug.file_path, "/home/user/app/parse.c");
+    assert_eq!(frame.debug.offset_in_file, Some(5));
+    assert_eq!(
+        sttr.app_frame(&[]).unwrap().debug.file_path,
+        "./nptl/pthread_kill.c"
+    );
+}
+
+#[test]
+fn test_stacktrace_thread_notices() {
+    let trace = "[New Thread 0x7ffff7d8a640 (LWP 42)]
+#0  0x0000555555555169 in worker (arg=0x0) at /tmp/test.c:5
+[Thread 0x7ffff7589640 (LWP 43) exited]
+#1  0x00007ffff7e2a76f in start_thread (arg=<optimized out>) at ./nptl/pthread_create.c:442
+[Inferior 1 (process 41) exited with code 01]";
+    let sttr = Stacktrace::from_gdb(trace).unwrap();
+    assert_eq!(sttr.strace.len(), 2);
+    assert_eq!(
+        sttr.strace[1].function_name().as_deref(),
+        Some("start_thread")
+    );
+}
+
+#[test]
+fn test_set_args_command() {
+    let args = ["plain", "with space", "it's", "", "$HOME;`id`", "a\"b\\c"];
+    let cmd = set_args_command(&args);
+    assert!(cmd.starts_with("set args plain 'with space' 'it'\\''s' ''"));
+
+    // Check that shell gets the same arguments
+    let output = std::process::Command::new("sh")
+        .arg("-c")
+        .arg(format!(
+            "printf '%s\\0' {}",
+            cmd.strip_prefix("set args ").unwrap()
+        ))
+        .output()
+        .unwrap();
+    let parsed = String::from_utf8(output.stdout).unwrap();
+    let parsed = parsed
+        .strip_suffix('\0')
+        .unwrap()
+        .split('\0')
+        .collect::<Vec<&str>>();
+    assert_eq!(parsed, args);
+}
+
+#[test]
+fn test_on_breakpoint() {
+    with_local("tests/bins/test_abort", &[], |cmd| {
+        cmd.on_breakpoint("test.c:5", &["p \"x\"", "continue"]);
+        assert!(has_ex(
+            &cmd.command_line().unwrap(),
+            r#"python gdb.Breakpoint("test.c:5").commands = "p \"x\"\ncontinue""#
+        ));
+        assert!(cmd.is_empty());
+    });
+}
+
+#[test]
+fn test_memory_bytes() {
+    let output = "0x555555555169 <main+4>:\t0xc7\t0x00\t0x2a\t0x00\t0x00\t0x00\t0x00\t0x00\n0x555555555171 <main+12>:\t0x90\t0xc3";
+    assert_eq!(
+        memory_bytes(output),
+        vec![0xc7, 0x00, 0x2a, 0x00, 0x00, 0x00, 0x00, 0x00, 0x90, 0xc3]
+    );
+    assert!(memory_bytes("Cannot access memory at address 0x0").is_empty());
+    assert_eq!(
+        memory_bytes("0x7ffff7ffeffe:\t0x01\t0x02\nCannot access memory at address 0x7ffff7fff000"),
+        vec![0x01, 0x02]
+    );
+}
+
+#[test]
+fn test_marker_style() {
+    with_local("tests/bins/test_abort", &[], |cmd| {
+        cmd.bt().set_marker_style(MarkerStyle::Echo).regs();
+        let argv = cmd.command_line().unwrap();
+        // Two commands and the final marker
+        assert_eq!(
+            argv.iter()
+                .filter(|x| x.starts_with("echo gdb-command-"))
+                .count(),
+            3
+        );
+        assert!(!argv.iter().any(|x| x.starts_with("p \"gdb-command-")));
+        assert_eq!(cmd.commands_len(), 2);
+    });
+}
+
+#[test]
+fn test_frame_numbers() {
+    let trace = [
+        "#0  0x0000555555555169 in worker () at /tmp/test.c:5",
+        "#1  0x00007ffff7e2a76f in start_thread () at ./nptl/pthread_create.c:442",
+        "#0  0x00007ffff7e98a7d in __futex_abstimed_wait_common () at ./nptl/futex-internal.c:57",
+        "#3  0x0000555555555189 in main () at /tmp/test.c:10",
+    ]
+    .join("\n");
+    let sttr = Stacktrace::from_gdb(&trace).unwrap();
+    let numbers = sttr
+        .strace
+        .iter()
+        .map(|x| x.frame_number)
+        .collect::<Vec<u32>>();
+    assert_eq!(numbers, vec![0, 1, 0, 3]);
+}
+
+#[test]
+fn test_architecture() {
+    assert_eq!(
+        architecture("The target architecture is set to \"auto\" (currently \"i386:x86-64\")."),
+        Some("i386:x86-64".to_string())
+    );
+    assert_eq!(
+        architecture("The target architecture is set automatically (currently i386)"),
+        Some("i386".to_string())
+    );
+    assert_eq!(
+        architecture("The target architecture is set to \"aarch64\"."),
+        Some("aarch64".to_string())
+    );
+    assert_eq!(
+        architecture("The target architecture is assumed to be i386:x86-64"),
+        Some("i386:x86-64".to_string())
+    );
+    assert_eq!(architecture("No symbol table is loaded."), None);
+
+    let bin = abs_path("tests/bins/test_abort");
+    let args = [bin.as_str()];
+    let exec_type = ExecType::Local(&args);
+    let mut cmd = GdbCommand::new(&exec_type);
+    cmd.show_architecture();
+    assert!(has_ex(&cmd.command_line().unwrap(), "show architecture"));
+}
+
+#[test]
+fn test_has_debug_info() {
+    let result = with_local("tests/bins/test_abort", &[], |cmd| cmd.has_debug_info());
+    assert!(matches!(result, Ok(true)), "{:?}", result);
+
+    // Symbol table of stripped binary isn't an error
+    let result = with_local("tests/bins/test_abort_stripped", &[], |cmd| {
+        cmd.has_debug_info()
+    });
+    assert!(matches!(result, Ok(false)), "{:?}", result);
+}
+
+#[test]
+fn test_has_debug_sources() {
+    let output = "/tmp/test:
+
+/tmp/test.c, /usr/include/stdio.h
+
+/lib/x86_64-linux-gnu/libc.so.6:
+(Objfile has no debugging information.)";
+    assert!(has_debug_sources(output));
+    let output = "/tmp/test:
+(Objfile has no debugging information.)";
+    assert!(!has_debug_sources(output));
+    let output = "Source files for which symbols have been read in:
+
+/tmp/test.c
+
+Source files for which symbols will be read in on demand:
+
+";
+    assert!(has_debug_sources(output));
+    assert!(!has_debug_sources(
+        "No symbol table is loaded.  Use the \"file\" command."
+    ));
+}
+
+#[test]
+fn test_rust_panic_frame() {
+    // panic=abort
+    let trace = [
+        "#0  __pthread_kill_implementation (no_tid=0, signo=6, threadid=<optimized out>) at ./nptl/pthread_kill.c:44",
+        "#1  0x00007ffff7d8e476 in __GI_raise (sig=sig@entry=6) at ../sysdeps/posix/raise.c:26",
+        "#2  0x00007ffff7d747f3 in __GI_abort () at ./stdlib/abort.c:79",
+        "#3  0x000055555556b44a in std::sys::pal::unix::abort_internal () at library/std/src/sys/pal/unix/mod.rs:366",
+        "#4  0x0000555555569f4a in std::process::abort () at library/std/src/process.rs:2369",
+        "#5  0x000055555555ebe4 in panic_abort::__rust_start_panic::abort () at library/panic_abort/src/lib.rs:49",
+        "#6  0x000055555555ebd6 in panic_abort::__rust_start_panic () at library/panic_abort/src/lib.rs:44",
+        "#7  0x000055555555e7a9 in std::panicking::rust_panic () at library/std/src/panicking.rs:862",
+        "#8  0x000055555555e6b2 in std::panicking::rust_panic_with_hook () at library/std/src/panicking.rs:826",
+        "#9  0x000055555555e4a8 in std::panicking::begin_panic_handler::{closure#0} () at library/std/src/panicking.rs:667",
+        "#10 0x000055555555d2d9 in std::sys::backtrace::__rust_end_short_backtrace<std::panicking::begin_panic_handler::{closure_env#0}, !> (f=...) at library/std/src/sys/backtrace.rs:168",
+        "#11 0x000055555555e1ec in std::panicking::begin_panic_handler (info=0x7fffffffd9a0) at library/std/src/panicking.rs:665",
+        "#12 0x0000555555572c05 in core::panicking::panic_fmt (fmt=...) at library/core/src/panicking.rs:74",
+        "#13 0x000055555557307d in core::result::unwrap_failed (msg=..., error=...) at library/core/src/result.rs:1700",
+        "#14 0x0000555555559b4e in core::result::Result<i32, core::num::error::ParseIntError>::unwrap<i32, core::num::error::ParseIntError> (self=...) at /rustc/library/core/src/result.rs:1104",
+        "#15 0x0000555555559c80 in test::main () at src/main.rs:3",
+        "#16 0x0000555555559a3b in core::ops::function::FnOnce::call_once<fn(), ()> () at /rustc/library/core/src/ops/function.rs:250",
+        "#17 0x000055555555a1d1 in std::panicking::try<i32, std::rt::lang_start_internal::{closure_env#2}> () at library/std/src/panicking.rs:552",
+    ]
+    .join("\n");
+    let sttr = Stacktrace::from_gdb(&trace).unwrap();
+    let frame = sttr.rust_panic_frame().unwrap();
+    assert_eq!(frame.function_name().unwrap(), "test::main");
+    assert_eq!(frame.debug.offset_in_file, Some(3));
+
+    // unwinding build stopped at rust_panic
+    let trace = [
+        "#0  0x000055555555e7a9 in std::panicking::rust_panic () at library/std/src/panicking.rs:862",
+        "#1  0x0000555555572c05 in core::panicking::panic_fmt (fmt=...) at library/core/src/panicking.rs:74",
+        "#2  0x0000555555559c80 in test::parse (s=...) at src/main.rs:7",
+        "#3  0x0000555555559d00 in test::main () at src/main.rs:3",
+    ]
+    .join("\n");
+    let sttr = Stacktrace::from_gdb(&trace).unwrap();
+    assert_eq!(
+        sttr.rust_panic_frame().unwrap().function_name().unwrap(),
+        "test::parse"
+    );
+
+    let trace = "#0  0x0000555555555169 in main () at /tmp/test.c:5";
+    assert!(Stacktrace::from_gdb(trace)
+        .unwrap()
+        .rust_panic_frame()
+        .is_none());
+}
+
+#[test]
+fn test_tls_values() {
+    let output = "
+Thread 2 (Thread 0x7ffff7d86640 (LWP 1235) \"test\"):
+$1 = 0
+
+Thread 1 (Thread 0x7ffff7d87740 (LWP 1234) \"test\"):
+$2 = 11";
+    let values = tls_values(output).unwrap();
+    assert_eq!(values.len(), 2);
+    assert_eq!(values[&1], "11");
+    assert_eq!(values[&2], "0");
+
+    let output = "
+Thread 1 (process 1234):
+Cannot find thread-local storage for process 1234, executable file /tmp/test:
+Cannot find thread-local variables on this target";
+    let err = tls_values(output).unwrap_err();
+    assert!(matches!(err, error::Error::TlsUnavailable(_)));
+    assert!(err.to_string().contains("process 1234"));
+}
+
+#[test]
+fn test_dump_memory() {
+    let bin = abs_path("tests/bins/test_abort");
+    let dump = std::env::temp_dir().join(format!("gdb-command-dump-{}.bin", std::process::id()));
+    let _ = std::fs::remove_file(&dump);
+    let args = [bin.as_str(), "A"];
+    let exec_type = ExecType::Local(&args);
+    let result = GdbCommand::new(&exec_type)
+        .r()
+        .dump_memory(&dump, "$sp", "$sp+16")
+        .run();
+    assert!(result.is_ok(), "{:?}", result);
+    assert_eq!(std::fs::metadata(&dump).unwrap().len(), 16);
+    std::fs::remove_file(&dump).unwrap();
+
+    // No process, nothing is dumped
+    let result = GdbCommand::new(&exec_type)
+        .dump_memory(&dump, "$sp", "$sp+16")
+        .run();
+    assert!(matches!(result, Err(error::Error::CommandFailed { .. })));
+
+    // Stale file from previous run isn't taken for result of failed dump
+    std::fs::write(&dump, b"stale").unwrap();
+    let result = GdbCommand::new(&exec_type)
+        .r()
+        .dump_memory(&dump, "$sp+16", "$sp")
+        .run();
+    assert!(matches!(result, Err(error::Error::CommandFailed { .. })));
+    assert!(!dump.exists());
+}
+
+#[test]
+fn test_invalid_core() {
+    let bin = abs_path("tests/bins/test_canary");
+    let core = std::env::temp_dir().join(format!("gdb-command-core-{}", std::process::id()));
+    std::fs::write(&core, b"").unwrap();
+    let core_path = core.to_str().unwrap();
+    let result = GdbCommand::new(&ExecType::Core {
+        target: &bin,
+        core: core_path,
+    })
+    .bt()
+    .run();
+    assert!(matches!(result, Err(error::Error::InvalidCore(_))));
+
+    std::fs::write(&core, b"\x7fELF\x02\x01\x01\x00").unwrap();
+    let result = GdbCommand::new(&ExecType::CoreOnly(core_path)).bt().run();
+    assert!(matches!(result, Err(error::Error::InvalidCore(_))));
+    std::fs::remove_file(&core).unwrap();
+
+    // Executable isn't a core
+    let result = GdbCommand::new(&ExecType::Core {
+        target: &bin,
+        core: &bin,
+    })
+    .bt()
+    .run_batch(&[&bin]);
+    assert!(matches!(result, Err(error::Error::InvalidCore(_))));
+}
+
+#[test]
+fn test_saved_core() {
+    assert_eq!(
+        saved_core("Saved corefile /tmp/core.1234").unwrap(),
+        std::path::PathBuf::from("/tmp/core.1234")
+    );
+    let err = saved_core("Failed to open '/root/core.1234' for output.").unwrap_err();
+    assert!(err.to_string().contains("Failed to open"));
+
+    let core = std::path::PathBuf::from("/tmp/my core");
+    let result = with_local("tests/bins/test_abort", &[], |cmd| {
+        cmd.generate_core(&core).run()
+    });
+    assert!(matches!(result, Err(error::Error::InvalidPath(_))));
+}
+
+#[test]
+fn test_generate_core_stale() {
+    // Program exited, stale core from previous run isn't taken for result
+    let core = std::env::temp_dir().join(format!("gdb-command-stale-core-{}", std::process::id()));
+    std::fs::write(&core, b"stale").unwrap();
+    let result = with_local("/bin/true", &[], |cmd| cmd.generate_core(&core).run());
+    assert!(matches!(result, Err(error::Error::CommandFailed { .. })));
+    assert!(!core.exists());
+}
+
+#[test]
+fn test_frame_info() {
+    let output = "Stack level 1, frame at 0x7fffffffe0f0:
+ rip = 0x555555555169 in main (/tmp/test.c:5); saved rip = 0x7ffff7df0083
+ caller of frame at 0x7fffffffe0c0
+ called by frame at 0x7fffffffe1c0
+ source language c.
+ Arglist at 0x7fffffffe0e0, args: 
+ Locals at 0x7fffffffe0e0, Previous frame's sp is 0x7fffffffe0f0
+ Saved registers:
+  rbx at 0x7fffffffe0d8, rbp at 0x7fffffffe0e0,
+  rip at 0x7fffffffe0e8";
+    let info = FrameInfo::from_gdb(output).unwrap();
+    assert_eq!(info.level, 1);
+    assert_eq!(info.cfa, 0x7fffffffe0f0);
+    assert_eq!(info.pc, 0x555555555169);
+    assert_eq!(info.saved_pc, Some(0x7ffff7df0083));
+    assert_eq!(info.callee, Some(0x7fffffffe0c0));
+    assert_eq!(info.caller, Some(0x7fffffffe1c0));
+    assert_eq!(info.saved_registers.len(), 3);
+    assert_eq!(info.saved_registers["rip"], 0x7fffffffe0e8);
+
+    // Outermost frame of corrupted stack
+    let output = "Stack level 0, frame at 0x0:
+ pc = 0x0; saved pc = <not saved>
+ Outermost frame: outermost
+ Arglist at 0x7fffffffe0b8, args: 
+ Locals at 0x7fffffffe0b8, Previous frame's sp is 0x7fffffffe0c8";
+    let info = FrameInfo::from_gdb(output).unwrap();
+    assert_eq!(info.cfa, 0);
+    assert_eq!(info.saved_pc, None);
+    assert_eq!(info.caller, None);
+    assert!(info.saved_registers.is_empty());
+
+    assert!(matches!(
+        FrameInfo::from_gdb("No stack."),
+        Err(error::Error::FrameInfoParse(_))
+    ));
+}
+
+#[test]
+fn test_parse_environment() {
+    let output = "LANG=C.UTF-8
+LS_COLORS=rs=0:di=01;34:ln=01;36
+EMPTY=
+SCRIPT=line one
+line two
+PATH=/usr/bin:/bin";
+    let env = parse_environment(output);
+    assert_eq!(env.len(), 5);
+    assert_eq!(env["LANG"], "C.UTF-8");
+    assert_eq!(env["LS_COLORS"], "rs=0:di=01;34:ln=01;36");
+    assert_eq!(env["EMPTY"], "");
+    assert_eq!(env["SCRIPT"], "line one\nline two");
+    assert_eq!(env["PATH"], "/usr/bin:/bin");
+    assert!(parse_environment("").is_empty());
+}
+
+#[test]
+fn test_log_to() {
+    let log = std::env::temp_dir().join(format!("gdb-command-log-{}.txt", std::process::id()));
+    let result = with_local("tests/bins/test_abort", &["A"], |cmd| {
+        cmd.log_to(&log).r().bt();
+        // Logging is enabled before any other command, syntax depends on gdb version
+        let argv = cmd.command_line().unwrap();
+        assert_eq!(
+            argv[1..3],
+            ["-ex", &format!("set logging file {}", log.display())]
+        );
+        assert!(argv[6] == "set logging enabled on" || argv[6] == "set logging on");
+        cmd.run()
+    });
+    if let Err(e) = &result {
+        panic!("{}", e);
+    }
+    let transcript = std::fs::read_to_string(&log).unwrap();
+    std::fs::remove_file(&log).unwrap();
+    assert!(transcript.contains(&result.unwrap()[0]));
+}
+
+#[test]
+fn test_symbol_address() {
+    assert_eq!(
+        symbol_address("Symbol \"main\" is a function at address 0x555555555149."),
+        Some(0x555555555149)
+    );
+    assert_eq!(
+        symbol_address("Symbol \"g_buf\" is static storage at address 0x4010."),
+        Some(0x4010)
+    );
+    assert_eq!(
+        symbol_address(
+            "Symbol \"printf\" is at 0x7ffff7e0a6f0 in a file compiled without debugging."
+        ),
+        Some(0x7ffff7e0a6f0)
+    );
+    assert_eq!(
+        symbol_address("Symbol \"x\" is a variable at frame base reg $rbp offset 16+-20."),
+        None
+    );
+    assert_eq!(
+        symbol_address("No symbol \"foo\" in current context."),
+        None
+    );
+}
+
+#[test]
+fn test_asan_build_id() {
+    let trace = [
+        "    #0 0x55f1c7b8e1a9 in main /tmp/test.c:5:3 (BuildId: 1a2b3c4d)",
+        "    #1 0x7f1e2a3b4d8f  (/lib/x86_64-linux-gnu/libc.so.6+0x29d8f) (BuildId: 490fef8403240c91833978d494d39e537409b92e)",
+        "    #2 0x55f1c7b8e0c4 in _start (/tmp/test+0x10c4)",
+    ]
+    .join("\n");
+    let sttr = Stacktrace::from_gdb(&trace).unwrap();
+    assert_eq!(sttr.strace[0].build_id.as_deref(), Some("1a2b3c4d"));
+    assert_eq!(sttr.strace[0].debug.file_path, "/tmp/test.c");
+    assert_eq!(sttr.strace[0].debug.offset_in_file, Some(5));
+    assert_eq!(
+        sttr.strace[1].build_id.as_deref(),
+        Some("490fef8403240c91833978d494d39e537409b92e")
+    );
+    assert_eq!(
+        sttr.strace[1].debug.file_path,
+        "/lib/x86_64-linux-gnu/libc.so.6"
+    );
+    assert_eq!(sttr.strace[1].debug.offset_in_file, Some(0x29d8f));
+    assert_eq!(sttr.strace[2].build_id, None);
+    assert_eq!(sttr.strace[2].debug.file_path, "/tmp/test");
+}
+
+#[test]
+fn test_data_directory() {
+    let bin = abs_path("tests/bins/test_abort");
+    let args = [bin.as_str()];
+    let exec_type = ExecType::Local(&args);
+    let mut cmd = GdbCommand::new(&exec_type);
+    cmd.data_directory("/opt/app/gdb/share/gdb").bt();
+    assert_eq!(cmd.commands_len(), 1);
+    let argv = cmd.command_line().unwrap();
+    // gdb option, not a command (-ex), before the target
+    let pos = argv.iter().position(|x| x == "--data-directory").unwrap();
+    assert_eq!(argv[pos + 1], "/opt/app/gdb/share/gdb");
+    assert!(pos == 0 || argv[pos - 1] != "-ex");
+    assert!(pos < argv.iter().position(|x| x == "-ex").unwrap());
+    assert!(pos < argv.iter().position(|x| x == "--args").unwrap());
+    assert_eq!(argv.last().unwrap(), &bin);
+
+    // Target arguments aren't rewritten as gdb commands for old gdb versions
+    let argv = with_local(
+        "tests/bins/test_abort",
+        &["set logging enabled on"],
+        |cmd| cmd.command_line().unwrap(),
+    );
+    assert_eq!(argv.last().unwrap(), "set logging enabled on");
+}
+
+#[test]
+fn test_exploitability() {
+    let mappings = [
+        "process 1",
+        "Mapped address spaces:",
+        "",
+        "          Start Addr           End Addr       Size     Offset  Perms  objfile",
+        "      0x555555554000     0x555555555000     0x1000        0x0  r--p   /tmp/bin",
+        "      0x555555555000     0x555555556000     0x1000     0x1000  r-xp   /tmp/bin",
+        "      0x7ffffffde000     0x7ffffffff000    0x21000        0x0  rw-p   [stack]",
+    ]
+    .join("\n");
+    let report = |signal: &str, regs: &str, disassembly: &str| {
+        let stacktrace =
+            Stacktrace::from_gdb("#0  0x0000555555555131 in main () at /tmp/test.c:5").unwrap();
+        CrashReport {
+            crash_hash: stacktrace.crash_hash(),
+            stacktrace,
+            registers: Registers::from_gdb(regs).unwrap(),
+            mappings: MappedFiles::from_gdb(&mappings).unwrap(),
+            disassembly: Disassembly::from_gdb(disassembly).unwrap_or_default(),
+            stop_reason: Some(StopReason {
+                signal: signal.to_string(),
+                ..Default::default()
+            }),
+            corruption_check: None,
+        }
+    };
+    let pc = "rip            0x555555555131      0x555555555131 <main+8>";
+
+    // Write to wild pointer
+    let regs = format!(
+        "rax            0x4141414141414141  4702111234474983745\n{}",
+        pc
+    );
+    let dis = "=> 0x555555555131 <main+8>:\tmov    DWORD PTR [rax],0x1\n   0x555555555137 <main+14>:\tret";
+    assert_eq!(
+        report("SIGSEGV", &regs, dis).exploitability(),
+        Exploitability::Exploitable
+    );
+    // Read from wild pointer
+    let dis = "=> 0x555555555131 <main+8>:\tmov    edx,DWORD PTR [rax+0x8]";
+    assert_eq!(
+        report("SIGSEGV", &regs, dis).exploitability(),
+        Exploitability::Unknown
+    );
+    // Call via wild pointer (AT&T)
+    let dis = "=> 0x555555555131 <main+8>:\tcall   *0x8(%rax)";
+    assert_eq!(
+        report("SIGSEGV", &regs, dis).exploitability(),
+        Exploitability::Exploitable
+    );
+    // Near-null write
+    let regs = format!("rax            0x0                 0\n{}", pc);
+    let dis = "=> 0x555555555131 <main+8>:\tmovl   $0x1,0x10(%rax)";
+    assert_eq!(
+        report("SIGSEGV", &regs, dis).exploitability(),
+        Exploitability::NotExploitable
+    );
+    // PC control
+    let regs = "rip            0x4141414141414141  0x4141414141414141";
+    assert_eq!(
+        report("SIGSEGV", regs, "").exploitability(),
+        Exploitability::Exploitable
+    );
+    // Executing stack
+    let regs = "rip            0x7ffffffde100      0x7ffffffde100";
+    assert_eq!(
+        report("SIGSEGV", regs, "").exploitability(),
+        Exploitability::Exploitable
+    );
+
+    assert_eq!(
+        report("SIGFPE", pc, "").exploitability(),
+        Exploitability::NotExploitable
+    );
+    assert_eq!(
+        report("SIGABRT", pc, "").exploitability(),
+        Exploitability::NotExploitable
+    );
+    let mut abort = report("SIGABRT", pc, "");
+    abort.corruption_check = Some("__stack_chk_fail".to_string());
+    assert_eq!(abort.exploitability(), Exploitability::ProbablyExploitable);
+    abort.stop_reason = None;
+    assert_eq!(abort.exploitability(), Exploitability::Unknown);
+}
+
+#[test]
+fn test_stacktrace_without_addresses() {
+    // set print address off
+    let trace = [
+        "#0  __pthread_kill_implementation (no_tid=0, signo=6, threadid=<optimized out>) at ./nptl/pthread_kill.c:44",
+        "#1  __GI_raise (sig=sig@entry=6) at ../sysdeps/posix/raise.c:26",
+        "#2  abort () from /lib/x86_64-linux-gnu/libc.so.6",
+        "#3  main (argc=2, argv=<optimized out>) at test.c:10",
+        "#4  _start ()",
+        "#5  foo (/tmp/test+0x1189)",
+    ]
+    .join("\n");
+    let sttr = Stacktrace::from_gdb(&trace).unwrap();
+    assert_eq!(sttr.strace.len(), 6);
+    assert!(sttr.strace.iter().all(|x| x.address == 0));
+    assert_eq!(
+        sttr.strace[0].function_name().unwrap(),
+        "__pthread_kill_implementation"
+    );
+    assert_eq!(sttr.strace[1].debug.file_path, "../sysdeps/posix/raise.c");
+    assert_eq!(sttr.strace[1].debug.offset_in_file, Some(26));
+    assert_eq!(sttr.strace[2].function_name().unwrap(), "abort");
+    assert_eq!(
+        sttr.strace[2].debug.file_path,
+        "/lib/x86_64-linux-gnu/libc.so.6"
+    );
+    assert_eq!(
+        sttr.strace[3].arguments[0],
+        ("argc".to_string(), "2".to_string())
+    );
+    assert_eq!(sttr.strace[4].function_name().unwrap(), "_start");
+    assert_eq!(sttr.strace[5].debug.file_path, "/tmp/test");
+    assert_eq!(sttr.strace[5].debug.offset_in_file, Some(0x1189));
+
+    // Frames without address and debug info are distinguished by function
+    assert_ne!(sttr.strace[2], sttr.strace[4]);
+    let other = Stacktrace::from_gdb("#0  abort () from /lib/x86_64-linux-gnu/libc.so.6").unwrap();
+    assert_eq!(sttr.strace[2], other.strace[0]);
+    let first = Stacktrace::from_gdb(&trace.lines().take(3).collect::<Vec<_>>().join("\n"));
+    let second = Stacktrace::from_gdb(
+        &trace
+            .lines()
+            .take(3)
+            .collect::<Vec<_>>()
+            .join("\n")
+            .replace("abort", "exit"),
+    );
+    assert_ne!(first.unwrap().crash_hash(), second.unwrap().crash_hash());
+}
+
+#[test]
+fn test_frame_info_source_lines() {
+    // set print frame-info source-and-location
+    let trace = [
+        "#0  0x0000555555555149 in crash (p=0x0) at /tmp/test.c:4",
+        "4\t    *p = 1;",
+        "#1  0x0000555555555171 in main () at /tmp/test.c:10",
+        "10\t    crash(NULL);",
+    ]
+    .join("\n");
+    let sttr = Stacktrace::from_gdb(&trace).unwrap();
+    assert_eq!(sttr.strace.len(), 2);
+    assert_eq!(sttr.strace[1].debug.offset_in_file, Some(10));
+
+    let bin = abs_path("tests/bins/test_abort");
+    let args = [bin.as_str()];
+    let exec_type = ExecType::Local(&args);
+    let mut cmd = GdbCommand::new(&exec_type);
+    cmd.bt().print_frame_info("source-and-location");
+    let argv = cmd.command_line().unwrap();
+    // Setup command is emitted before commands even if it is set later
+    let setup = argv
+        .iter()
+        .position(|x| x == "set print frame-info source-and-location")
+        .unwrap();
+    assert_eq!(argv[setup - 1], "-ex");
+    assert!(setup < argv.iter().position(|x| x == "bt").unwrap());
+    assert_eq!(cmd.commands_len(), 1);
+}
+
+#[test]
+fn test_stacktrace_similarity() {
+    let trace = |lines: &[&str]| {
+        Stacktrace::from_gdb(
+            &lines
+                .iter()
+                .enumerate()
+                .map(|(i, x)| {
+                    format!(
+                        "#{}  0x0000555555555169 in {} () at /tmp/test.c:{}",
+                        i,
+                        x,
+                        i + 1
+                    )
+                })
+                .collect::<Vec<String>>()
+                .join("\n"),
+        )
+        .unwrap()
+    };
+    let a = trace(&["a", "b", "c", "d"]);
+    assert_eq!(a.similarity(&a), 1.0);
+    // Frames are compared by source location
+    let b = Stacktrace::from_gdb(
+        "#0  0x0000555555555169 in x () at /tmp/test.c:1\n#1  0x0000555555555169 in y () at /tmp/test.c:3",
+    )
+    .unwrap();
+    assert_eq!(a.similarity(&b), 2.0 * 2.0 / 6.0);
+    assert_eq!(b.similarity(&a), a.similarity(&b));
+    let c = Stacktrace::from_gdb("#0  0x0000555555555169 in x () at /tmp/other.c:1").unwrap();
+    assert_eq!(a.similarity(&c), 0.0);
+
+    let empty = Stacktrace { strace: Vec::new() };
+    assert_eq!(empty.similarity(&empty), 1.0);
+    assert_eq!(a.similarity(&empty), 0.0);
+}
+
+#[test]
+fn test_apply_all() {
+    let output = "
+Thread 2 (Thread 0x7ffff7d86640 (LWP 1235) \"worker\"):
+#0  0x00007ffff7e98a7d in __futex_abstimed_wait_common () at ./nptl/futex-internal.c:57
+#1  0x00007ffff7e2a76f in start_thread () at ./nptl/pthread_create.c:442
+
+Thread 1.1 (Thread 0x7ffff7d87740 (LWP 1234) \"test\"):
+#0  0x0000555555555169 in main () at /tmp/test.c:5";
+    let threads = split_threads(output).unwrap();
+    assert_eq!(threads.len(), 2);
+    assert_eq!(threads[0].0, "2");
+    assert_eq!(Stacktrace::from_gdb(&threads[0].1).unwrap().strace.len(), 2);
+    assert_eq!(threads[1].0, "1.1");
+    assert!(threads[1].1.starts_with("#0  0x0000555555555169 in main"));
+    assert!(split_threads("").unwrap().is_empty());
+    assert!(matches!(
+        split_threads("#0  0x0000555555555169 in main ()"),
+        Err(error::Error::ThreadsParse(_))
+    ));
+
+    let output = "#0  0x0000555555555149 in crash (p=0x0) at /tmp/test.c:4
+$1 = (void *) 0x7fffffffe0b0
+#1  0x0000555555555171 in main () at /tmp/test.c:10
+$2 = (void *) 0x7fffffffe0d0";
+    let frames = split_frames(output);
+    assert_eq!(
+        frames,
+        vec![
+            (0, "$1 = (void *) 0x7fffffffe0b0".to_string()),
+            (1, "$2 = (void *) 0x7fffffffe0d0".to_string())
+        ]
+    );
+
+    let bin = abs_path("tests/bins/test_abort");
+    let args = [bin.as_str()];
+    let exec_type = ExecType::Local(&args);
+    let mut cmd = GdbCommand::new(&exec_type);
+    cmd.apply_all_threads("bt")
+        .apply_all_frames("p $sp")
+        .print_tls("errno");
+    assert_eq!(cmd.commands_len(), 3);
+    let argv = cmd.command_line().unwrap();
+    assert!(has_ex(&argv, "thread apply all bt"));
+    assert!(has_ex(&argv, "frame apply all p $sp"));
+    assert!(has_ex(&argv, "thread apply all p errno"));
+}
+
+#[test]
+fn test_disassembly() {
+    let output = [
+        "=> 0x555555555131 <main+8>:\trep stos QWORD PTR es:[rdi],rax",
+        "   0x555555555134 <std::vector<int>::size() const+4>:\tcall   0x555555555030 <puts@plt>",
+        "   0x401000:\tret",
+    ]
+    .join("\n");
+    let disassembly = Disassembly::from_gdb(&output).unwrap();
+    assert_eq!(disassembly.instructions.len(), 3);
+    let current = disassembly.current().unwrap();
+    assert_eq!(current.address, 0x555555555131);
+    assert_eq!(current.symbol, "main+8");
+    assert_eq!(current.mnemonic, "stos");
+    assert_eq!(current.operands, ["QWORD PTR es:[rdi]", "rax"]);
+    let call = &disassembly.instructions[1];
+    assert!(!call.current);
+    assert_eq!(call.symbol, "std::vector<int>::size() const+4");
+    assert_eq!(call.operands, ["0x555555555030 <puts@plt>"]);
+    assert_eq!(disassembly.instructions[2].symbol, "");
+    assert!(disassembly.instructions[2].operands.is_empty());
+
+    // Program counter isn't readable
+    let disassembly =
+        Disassembly::from_gdb("=> 0x0:\tCannot access memory at address 0x0").unwrap();
+    assert!(disassembly.current().is_none());
+    assert!(matches!(
+        Disassembly::from_gdb("No registers."),
+        Err(error::Error::DisassemblyParse(_))
+    ));
+}
+
+#[test]
+fn test_fail_on_gdb_warning() {
+    with_local("tests/bins/test_abort", &["A"], |cmd| {
+        cmd.python("import sys\nsys.stderr.write('warning: no debug info\\n')");
+        let result = cmd.run();
+        if let Err(e) = &result {
+            panic!("{}", e);
+        }
+        let result = cmd.fail_on_gdb_warning().run();
+        match result {
+            Err(error::Error::GdbWarning(warnings)) => {
+                assert_eq!(warnings, vec!["warning: no debug info".to_string()])
+            }
+            _ => panic!("{:?}", result),
+        }
+    });
+}
+
+#[test]
+fn test_gdb_value() {
+    assert_eq!(GdbValue::parse("$1 = 5"), GdbValue::Int(5));
+    assert_eq!(GdbValue::parse("$2 = -1"), GdbValue::Int(-1));
+    assert_eq!(GdbValue::parse("$3 = 65 'A'"), GdbValue::Int(65));
+    assert_eq!(GdbValue::parse("$4 = 0x1f"), GdbValue::Int(0x1f));
+    assert_eq!(
+        GdbValue::parse("$5 = 0x4006f4 \"say \\\"hi\\\"\""),
+        GdbValue::Str("say \"hi\"".to_string())
+    );
+    assert_eq!(
+        GdbValue::parse("$6 = (int *) 0x7fffffffe0bc"),
+        GdbValue::Ptr {
+            ty: Some("int *".to_string()),
+            address: 0x7fffffffe0bc
+        }
+    );
+    assert_eq!(
+        GdbValue::parse("$7 = (void (*)(int)) 0x555555555149 <handler>"),
+        GdbValue::Raw("(void (*)(int)) 0x555555555149 <handler>".to_string())
+    );
+    assert_eq!(
+        GdbValue::parse("$8 = 0x555555555149 <main>"),
+        GdbValue::Ptr {
+            ty: None,
+            address: 0x555555555149
+        }
+    );
+    assert_eq!(
+        GdbValue::parse("$9 = RED"),
+        GdbValue::Enum("RED".to_string())
+    );
+    assert_eq!(
+        GdbValue::parse("$10 = (FLAG_A | FLAG_C)"),
+        GdbValue::Enum("FLAG_A | FLAG_C".to_string())
+    );
+    assert_eq!(
+        GdbValue::parse(
+            "$11 = {a = 1, flags = 3, name = \"x, y\", next = 0x0, inner = {c = GREEN}}"
+        ),
+        GdbValue::Struct(vec![
+            ("a".to_string(), GdbValue::Int(1)),
+            ("flags".to_string(), GdbValue::Int(3)),
+            ("name".to_string(), GdbValue::Str("x, y".to_string())),
+            ("next".to_string(), GdbValue::Int(0)),
+            (
+                "inner".to_string(),
+                GdbValue::Struct(vec![("c".to_string(), GdbValue::Enum("GREEN".to_string()))])
+            ),
+        ])
+    );
+    assert_eq!(
+        GdbValue::parse("$12 = {1, 2, 3}"),
+        GdbValue::Raw("{1, 2, 3}".to_string())
+    );
+    assert_eq!(
+        GdbValue::parse("$13 = 1.5"),
+        GdbValue::Raw("1.5".to_string())
+    );
+}
+
+#[test]
+fn test_parse_trace() {
+    let gdb = "#0  0x00007ffff7a42428 in __GI_raise (sig=sig@entry=6) at raise.c:54\n\
+               #1  0x0000555555555189 in main () at /tmp/test.c:10\n";
+    let trace = parse_trace(gdb).unwrap();
+    assert_eq!(trace, Stacktrace::from_gdb(gdb).unwrap());
+
+    let asan = "=================================================================\n\
+        ==1234==ERROR: AddressSanitizer: heap-buffer-overflow on address 0x602000000011\n\
+        READ of size 1 at 0x602000000011 thread T0\n\
+            #0 0x4f1c2a in foo /tmp/test.c:5:12\n\
+            #1 0x4f1d3b in main /tmp/test.c:10:5\n\
+            #2 0x7f1e2a3b4c5d in __libc_start_main (/lib/x86_64-linux-gnu/libc.so.6+0x29d8f)\n\
+        \n\
+        0x602000000011 is located 0 bytes to the right of 1-byte region\n\
+        allocated by thread T0 here:\n\
+            #0 0x4b9f2d in malloc\n\
+            #1 0x4f1d10 in main /tmp/test.c:9:14\n";
+    let trace = parse_trace(asan).unwrap();
+    assert_eq!(trace.strace.len(), 3);
+    assert_eq!(trace.strace[0].address, 0x4f1c2a);
+    assert_eq!(trace.strace[1].debug.file_path, "/tmp/test.c");
+    assert_eq!(trace.strace[1].debug.offset_in_file, Some(10));
+    assert_eq!(trace.strace[2].debug.offset_in_file, Some(0x29d8f));
+
+    let valgrind = "==4321== Invalid read of size 4\n\
+        ==4321==    at 0x1091A4: foo (test.c:5)\n\
+        ==4321==    by 0x1091C9: main (test.c:10)\n\
+        ==4321==    by 0x48850B2: (below main) (in /lib/x86_64-linux-gnu/libc.so.6)\n\
+        ==4321==  Address 0x0 is not stack'd, malloc'd or (recently) free'd\n\
+        ==4321==    at 0x109000: bar (test.c:1)\n";
+    let trace = parse_trace(valgrind).unwrap();
+    assert_eq!(trace.strace.len(), 3);
+    assert_eq!(trace.strace[0].address, 0x1091a4);
+    assert_eq!(trace.strace[0].frame_number, 0);
+    assert_eq!(trace.strace[1].debug.file_path, "test.c");
+    assert_eq!(trace.strace[1].debug.offset_in_file, Some(10));
+    assert_eq!(
+        trace.strace[2].debug.file_path,
+        "/lib/x86_64-linux-gnu/libc.so.6"
+    );
+
+    assert!(parse_trace("==1==ERROR: AddressSanitizer: SEGV on unknown address\n").is_err());
+}
+
+#[test]
+fn test_stdin_bytes() {
+    // Print stdin file path and permissions, then stdin data
+    let script = "readlink /proc/$$/fd/0; stat -L -c %a /proc/$$/fd/0; cat";
+    let result = GdbCommand::new(&ExecType::Local(&["/bin/sh", "-c", script]))
+        .stdin_bytes(b"stdin data\n".to_vec())
+        .raw();
+    if let Err(e) = &result {
+        panic!("{}", e);
+    }
+    let output = String::from_utf8_lossy(&result.unwrap()).to_string();
+    let lines = output.lines().collect::<Vec<&str>>();
+    let pos = lines
+        .iter()
+        .position(|x| x.contains("gdb-command-stdin-"))
+        .unwrap();
+    let path = std::path::Path::new(lines[pos]);
+    assert!(path.starts_with(std::env::temp_dir()));
+    assert_eq!(lines[pos + 1], "600");
+    assert_eq!(lines[pos + 2], "stdin data");
+    // Temporary file is removed after gdb finishes
+    assert!(!path.exists());
 }